@@ -2,13 +2,18 @@
 use std::collections::HashSet;
 #[cfg(feature = "bluetooth")]
 use std::str::FromStr;
+use std::sync::Arc;
 
 use chezmoi_database::metrics::entity::Metric;
 #[cfg(feature = "bluetooth")]
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
 pub mod sensor;
+pub mod sink;
 pub mod watcher;
 
 pub const HOSTNAME: &str = "hostname";
@@ -29,11 +34,31 @@ pub struct Config {
     #[cfg(feature = "sensor-bt-scanner")]
     #[serde(default)]
     bt_scanner: sensor::ConfigWrapper<sensor::bt_scanner::Config>,
+    #[serde(default)]
+    disk: sensor::ConfigWrapper<sensor::disk::Config>,
+    #[serde(default)]
+    exec: sensor::ConfigWrapper<sensor::exec::Config>,
+    #[cfg(feature = "sensor-http-probe")]
+    #[serde(default)]
+    http_probe: sensor::ConfigWrapper<sensor::http_probe::Config>,
     #[cfg(feature = "sensor-miflora")]
     #[serde(default)]
     miflora: sensor::ConfigWrapper<sensor::miflora::Config>,
+    #[cfg(feature = "sensor-mqtt")]
+    #[serde(default)]
+    mqtt: sensor::ConfigWrapper<sensor::mqtt::Config>,
+    #[serde(default)]
+    network: sensor::ConfigWrapper<sensor::network::Config>,
+    #[serde(default)]
+    process: sensor::ConfigWrapper<sensor::process::Config>,
+    #[serde(default)]
+    reachability: sensor::ConfigWrapper<sensor::reachability::Config>,
     #[serde(default)]
     system: sensor::ConfigWrapper<sensor::system::Config>,
+    #[serde(default)]
+    thermal: sensor::ConfigWrapper<sensor::thermal::Config>,
+    #[serde(default)]
+    sinks: Vec<sink::Config>,
 }
 
 impl Config {
@@ -93,6 +118,31 @@ impl Config {
         }
     }
 
+    fn disk(&self) -> Option<sensor::disk::Sensor> {
+        if self.disk.enabled {
+            Some(self.disk.inner.build())
+        } else {
+            None
+        }
+    }
+
+    fn exec(&self) -> Option<sensor::exec::Sensor> {
+        if self.exec.enabled {
+            Some(self.exec.inner.build())
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "sensor-http-probe")]
+    fn http_probe(&self) -> anyhow::Result<Option<sensor::http_probe::Sensor>> {
+        if self.http_probe.enabled {
+            Ok(Some(self.http_probe.inner.build()?))
+        } else {
+            Ok(None)
+        }
+    }
+
     #[cfg(feature = "sensor-miflora")]
     fn miflora(&self, adapter: bluer::Adapter) -> Option<sensor::miflora::Sensor> {
         if self.miflora.enabled {
@@ -102,6 +152,39 @@ impl Config {
         }
     }
 
+    #[cfg(feature = "sensor-mqtt")]
+    fn mqtt(&self) -> Option<sensor::mqtt::Sensor> {
+        if self.mqtt.enabled {
+            Some(self.mqtt.inner.build())
+        } else {
+            None
+        }
+    }
+
+    fn network(&self) -> Option<sensor::network::Sensor> {
+        if self.network.enabled {
+            Some(self.network.inner.build())
+        } else {
+            None
+        }
+    }
+
+    fn process(&self) -> Option<sensor::process::Sensor> {
+        if self.process.enabled {
+            Some(self.process.inner.build())
+        } else {
+            None
+        }
+    }
+
+    fn reachability(&self) -> Option<sensor::reachability::Sensor> {
+        if self.reachability.enabled {
+            Some(self.reachability.inner.build())
+        } else {
+            None
+        }
+    }
+
     fn system(&self) -> Option<sensor::system::Sensor> {
         if self.system.enabled {
             Some(self.system.inner.build())
@@ -110,10 +193,23 @@ impl Config {
         }
     }
 
+    fn thermal(&self) -> Option<sensor::thermal::Sensor> {
+        if self.thermal.enabled {
+            Some(self.thermal.inner.build())
+        } else {
+            None
+        }
+    }
+
     pub async fn build(self) -> anyhow::Result<Agent> {
         #[cfg(feature = "bluetooth")]
         let bt_adapter = default_bt_adapter().await?;
 
+        let mut sinks = Vec::with_capacity(self.sinks.len());
+        for sink in self.sinks.iter() {
+            sinks.push(sink.build().await?);
+        }
+
         Ok(Agent {
             #[cfg(feature = "bluetooth")]
             bt_watcher: self.bt_watcher(bt_adapter.clone()),
@@ -122,36 +218,68 @@ impl Config {
             atc_thermometer: self.atc_thermometer(bt_adapter.clone()),
             #[cfg(feature = "sensor-bt-scanner")]
             bt_scanner: self.bt_scanner(bt_adapter.clone()),
+            disk: self.disk(),
+            exec: self.exec(),
+            #[cfg(feature = "sensor-http-probe")]
+            http_probe: self.http_probe()?,
             #[cfg(feature = "sensor-miflora")]
             miflora: self.miflora(bt_adapter.clone()),
+            #[cfg(feature = "sensor-mqtt")]
+            mqtt: self.mqtt(),
+            network: self.network(),
+            process: self.process(),
+            reachability: self.reachability(),
             system: self.system(),
+            thermal: self.thermal(),
+            sinks,
         })
     }
 }
 
 #[tracing::instrument(name = "collector", skip_all)]
 async fn collect(
-    database: chezmoi_database::Client,
+    sinks: Vec<Box<dyn sink::Sink>>,
     mut receiver: mpsc::Receiver<Vec<Metric>>,
 ) -> anyhow::Result<()> {
+    // each sink runs in its own task and a sink lagging behind loses the new batches,
+    // so that it doesn't block the others
+    let mut senders = Vec::with_capacity(sinks.len());
+    let mut tasks = Vec::with_capacity(sinks.len());
+    for sink in sinks {
+        let (sender, receiver) = mpsc::channel::<Arc<[Metric]>>(100);
+        senders.push((sink.name(), sender));
+        tasks.push(tokio::spawn(sink::run(sink, receiver)));
+    }
     while let Some(batch) = receiver.recv().await {
         if batch.is_empty() {
             continue;
         }
-        match chezmoi_database::metrics::entity::create::Command::new(&batch)
-            .execute(database.as_ref())
-            .await
-        {
-            Ok(count) => tracing::debug!(message = "stored events", count = count),
-            Err(error) => {
-                tracing::error!(message = "unable to store received metrics", cause = %error)
+        let batch: Arc<[Metric]> = Arc::from(batch);
+        for (name, sender) in senders.iter() {
+            match sender.try_send(batch.clone()) {
+                Ok(_) => {}
+                Err(mpsc::error::TrySendError::Full(batch)) => {
+                    tracing::warn!(
+                        message = "sink is lagging behind, dropping metrics",
+                        sink = name,
+                        count = batch.len()
+                    );
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    tracing::error!(message = "unable to forward received metrics", sink = name);
+                }
             }
         }
     }
+    drop(senders);
+    for task in tasks {
+        if let Err(error) = task.await {
+            tracing::error!(message = "unable to join sink", cause = %error);
+        }
+    }
     Ok(())
 }
 
-#[derive(Debug)]
 pub struct Agent {
     #[cfg(feature = "bluetooth")]
     bt_watcher: Option<watcher::bluetooth::Watcher>,
@@ -159,14 +287,64 @@ pub struct Agent {
     atc_thermometer: Option<sensor::atc_thermometer::Sensor>,
     #[cfg(feature = "sensor-bt-scanner")]
     bt_scanner: Option<sensor::bt_scanner::Sensor>,
+    disk: Option<sensor::disk::Sensor>,
+    exec: Option<sensor::exec::Sensor>,
+    #[cfg(feature = "sensor-http-probe")]
+    http_probe: Option<sensor::http_probe::Sensor>,
     #[cfg(feature = "sensor-miflora")]
     miflora: Option<sensor::miflora::Sensor>,
+    #[cfg(feature = "sensor-mqtt")]
+    mqtt: Option<sensor::mqtt::Sensor>,
+    network: Option<sensor::network::Sensor>,
+    process: Option<sensor::process::Sensor>,
+    reachability: Option<sensor::reachability::Sensor>,
     system: Option<sensor::system::Sensor>,
+    thermal: Option<sensor::thermal::Sensor>,
+    sinks: Vec<Box<dyn sink::Sink>>,
+}
+
+impl std::fmt::Debug for Agent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut res = f.debug_struct("Agent");
+        #[cfg(feature = "bluetooth")]
+        res.field("bt_watcher", &self.bt_watcher);
+        #[cfg(feature = "sensor-atc-thermometer")]
+        res.field("atc_thermometer", &self.atc_thermometer);
+        #[cfg(feature = "sensor-bt-scanner")]
+        res.field("bt_scanner", &self.bt_scanner);
+        res.field("disk", &self.disk);
+        res.field("exec", &self.exec);
+        #[cfg(feature = "sensor-http-probe")]
+        res.field("http_probe", &self.http_probe);
+        #[cfg(feature = "sensor-miflora")]
+        res.field("miflora", &self.miflora);
+        #[cfg(feature = "sensor-mqtt")]
+        res.field("mqtt", &self.mqtt);
+        res.field("network", &self.network)
+            .field("process", &self.process)
+            .field("reachability", &self.reachability)
+            .field("system", &self.system)
+            .field("thermal", &self.thermal)
+            .field(
+                "sinks",
+                &self
+                    .sinks
+                    .iter()
+                    .map(|sink| sink.name())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
 }
 
 impl Agent {
-    pub async fn run(self, database: chezmoi_database::Client) -> anyhow::Result<()> {
-        let (sender, receiver) = mpsc::channel::<Vec<Metric>>(100);
+    /// Adds a destination for the collected metrics, on top of the configured ones.
+    pub fn with_sink<S: sink::Sink + 'static>(mut self, sink: S) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    fn spawn(&mut self, sender: mpsc::Sender<Vec<Metric>>) -> Vec<JoinHandle<anyhow::Result<()>>> {
         #[cfg(feature = "bluetooth")]
         let (bt_sender, bt_receiver) = broadcast::channel::<watcher::bluetooth::WatcherEvent>(100);
 
@@ -174,38 +352,74 @@ impl Agent {
 
         let mut tasks = Vec::new();
         #[cfg(feature = "sensor-atc-thermometer")]
-        if let Some(sensor) = self.atc_thermometer {
+        if let Some(sensor) = self.atc_thermometer.take() {
             let ctx = context.clone();
             let rcv = bt_receiver.resubscribe();
             tasks.push(tokio::spawn(async move { sensor.run(ctx, rcv).await }));
         }
         #[cfg(feature = "sensor-bt-scanner")]
-        if let Some(sensor) = self.bt_scanner {
+        if let Some(sensor) = self.bt_scanner.take() {
             let ctx = context.clone();
             let rcv = bt_receiver.resubscribe();
             tasks.push(tokio::spawn(async move { sensor.run(ctx, rcv).await }));
         }
+        if let Some(sensor) = self.disk.take() {
+            let ctx = context.clone();
+            tasks.push(tokio::spawn(async move { sensor.run(ctx).await }));
+        }
+        if let Some(sensor) = self.exec.take() {
+            let ctx = context.clone();
+            tasks.push(tokio::spawn(async move { sensor.run(ctx).await }));
+        }
+        #[cfg(feature = "sensor-http-probe")]
+        if let Some(sensor) = self.http_probe.take() {
+            let ctx = context.clone();
+            tasks.push(tokio::spawn(async move { sensor.run(ctx).await }));
+        }
         #[cfg(feature = "sensor-miflora")]
-        if let Some(sensor) = self.miflora {
+        if let Some(sensor) = self.miflora.take() {
             let ctx = context.clone();
             let rcv = bt_receiver.resubscribe();
             tasks.push(tokio::spawn(async move { sensor.run(ctx, rcv).await }));
         }
-        if let Some(sensor) = self.system {
+        #[cfg(feature = "sensor-mqtt")]
+        if let Some(sensor) = self.mqtt.take() {
+            let ctx = context.clone();
+            tasks.push(tokio::spawn(async move { sensor.run(ctx).await }));
+        }
+        if let Some(sensor) = self.network.take() {
+            let ctx = context.clone();
+            tasks.push(tokio::spawn(async move { sensor.run(ctx).await }));
+        }
+        if let Some(sensor) = self.process.take() {
+            let ctx = context.clone();
+            tasks.push(tokio::spawn(async move { sensor.run(ctx).await }));
+        }
+        if let Some(sensor) = self.reachability.take() {
+            let ctx = context.clone();
+            tasks.push(tokio::spawn(async move { sensor.run(ctx).await }));
+        }
+        if let Some(sensor) = self.system.take() {
+            let ctx = context.clone();
+            tasks.push(tokio::spawn(async move { sensor.run(ctx).await }));
+        }
+        if let Some(sensor) = self.thermal.take() {
             let ctx = context.clone();
             tasks.push(tokio::spawn(async move { sensor.run(ctx).await }));
         }
 
         #[cfg(feature = "bluetooth")]
-        if let Some(watcher) = self.bt_watcher {
+        if let Some(watcher) = self.bt_watcher.take() {
             let ctx = context.clone();
             tasks.push(tokio::spawn(
                 async move { watcher.run(ctx, bt_sender).await },
             ));
         }
 
-        collect(database, receiver).await?;
+        tasks
+    }
 
+    async fn join(mut tasks: Vec<JoinHandle<anyhow::Result<()>>>) {
         while let Some(sensor) = tasks.pop() {
             match sensor.await {
                 Ok(Ok(_)) => {}
@@ -213,7 +427,85 @@ impl Agent {
                 Err(inner) => tracing::error!(message = "unable to join taask", cause = %inner),
             }
         }
+    }
 
+    /// Runs the sensors and sends the collected metrics to the sinks.
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        if self.sinks.is_empty() {
+            anyhow::bail!("no sink configured, collected metrics would be lost");
+        }
+        let (sender, receiver) = mpsc::channel::<Vec<Metric>>(100);
+        let tasks = self.spawn(sender);
+        collect(self.sinks, receiver).await?;
+        Self::join(tasks).await;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use chezmoi_database::metrics::entity::{Metric, MetricValue};
+    use chezmoi_database::metrics::MetricHeader;
+    use futures::future::BoxFuture;
+
+    /// Sink never completing a write, like an unreachable server without timeout.
+    struct StalledSink;
+
+    impl crate::sink::Sink for StalledSink {
+        fn name(&self) -> &'static str {
+            "stalled"
+        }
+
+        fn write<'a>(&'a mut self, _batch: &'a [Metric]) -> BoxFuture<'a, anyhow::Result<()>> {
+            Box::pin(futures::future::pending())
+        }
+    }
+
+    struct CountingSink(Arc<AtomicUsize>);
+
+    impl crate::sink::Sink for CountingSink {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        fn write<'a>(&'a mut self, _batch: &'a [Metric]) -> BoxFuture<'a, anyhow::Result<()>> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn should_not_block_on_stalled_sink() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = tokio::sync::mpsc::channel(10);
+        tokio::spawn(super::collect(
+            vec![
+                Box::new(StalledSink),
+                Box::new(CountingSink(counter.clone())),
+            ],
+            receiver,
+        ));
+        for timestamp in 0..500 {
+            let batch = vec![Metric {
+                timestamp,
+                header: MetricHeader::new("system.cpu"),
+                value: MetricValue::gauge(12.5),
+            }];
+            tokio::time::timeout(Duration::from_secs(1), sender.send(batch))
+                .await
+                .expect("collector should not be blocked")
+                .unwrap();
+        }
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while counter.load(Ordering::Relaxed) < 500 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("every batch should reach the counting sink");
+    }
+}
@@ -0,0 +1,42 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+fn enable_tracing() {
+    use tracing_subscriber::prelude::*;
+    use tracing_subscriber::EnvFilter;
+
+    if tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| "chezmoi_agent=debug".into()))
+        .with(tracing_subscriber::fmt::layer())
+        .try_init()
+        .is_err()
+    {
+        tracing::warn!("tracing already set");
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct RootConfig {
+    agent: chezmoi_agent::Config,
+}
+
+impl RootConfig {
+    fn from_path(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::de::from_str(content.as_str())?)
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    enable_tracing();
+
+    let root_path = PathBuf::from("./chezmoi.toml");
+    let RootConfig { agent } =
+        RootConfig::from_path(&root_path).context("loading configuration")?;
+
+    let agent = agent.build().await.context("building agent")?;
+
+    agent.run().await
+}
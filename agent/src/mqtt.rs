@@ -0,0 +1,164 @@
+//! Connection to an MQTT broker, shared by the mqtt sink and sensor.
+
+use std::time::Duration;
+
+fn default_port() -> u16 {
+    1883
+}
+
+fn default_keep_alive() -> u64 {
+    30
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct BrokerConfig {
+    host: String,
+    #[serde(default = "default_port")]
+    port: u16,
+    /// Identifier of the client, should be unique for the broker.
+    #[serde(default)]
+    client_id: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    /// Keep alive interval in seconds.
+    #[serde(default = "default_keep_alive")]
+    keep_alive: u64,
+}
+
+impl BrokerConfig {
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            client_id: None,
+            username: None,
+            password: None,
+            keep_alive: default_keep_alive(),
+        }
+    }
+
+    pub(crate) fn options(&self, default_client_id: &str) -> rumqttc::MqttOptions {
+        let client_id = self.client_id.as_deref().unwrap_or(default_client_id);
+        let mut options = rumqttc::MqttOptions::new(client_id, self.host.as_str(), self.port);
+        options.set_keep_alive(Duration::from_secs(self.keep_alive.max(5)));
+        if let Some(ref username) = self.username {
+            options.set_credentials(username, self.password.as_deref().unwrap_or_default());
+        }
+        options
+    }
+}
+
+/// Minimal broker, accepting any client and forwarding the publications to the subscribers.
+#[cfg(test)]
+pub(crate) mod broker {
+    use std::net::SocketAddr;
+    use std::sync::{Arc, Mutex};
+
+    use bytes::BytesMut;
+    use rumqttc::mqttbytes::v4::{self, Packet};
+    use rumqttc::mqttbytes::{matches, Error};
+    use rumqttc::{
+        ConnAck, ConnectReturnCode, PingResp, PubAck, Publish, QoS, SubAck, SubscribeReasonCode,
+    };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::broadcast;
+
+    #[derive(Clone)]
+    pub(crate) struct Broker {
+        pub address: SocketAddr,
+        pub received: Arc<Mutex<Vec<Publish>>>,
+        sender: broadcast::Sender<Publish>,
+    }
+
+    impl Broker {
+        pub async fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (sender, _) = broadcast::channel(100);
+            let broker = Self {
+                address: listener.local_addr().unwrap(),
+                received: Default::default(),
+                sender,
+            };
+            let cloned = broker.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(cloned.clone().handle(stream));
+                }
+            });
+            broker
+        }
+
+        /// Sends a message to the clients subscribed to the topic.
+        #[cfg(feature = "sensor-mqtt")]
+        pub fn publish(&self, topic: &str, payload: &str) {
+            let _ = self
+                .sender
+                .send(Publish::new(topic, QoS::AtMostOnce, payload));
+        }
+
+        async fn handle(self, mut stream: TcpStream) {
+            let mut receiver = self.sender.subscribe();
+            let mut filters = Vec::<String>::new();
+            let mut input = BytesMut::new();
+            let mut output = BytesMut::new();
+            loop {
+                let packet = match v4::read(&mut input, 1024 * 1024) {
+                    Ok(packet) => packet,
+                    Err(Error::InsufficientBytes(_)) => {
+                        tokio::select! {
+                            read = stream.read_buf(&mut input) => match read {
+                                Ok(0) | Err(_) => return,
+                                Ok(_) => continue,
+                            },
+                            Ok(publish) = receiver.recv() => {
+                                if filters.iter().any(|filter| matches(&publish.topic, filter)) {
+                                    publish.write(&mut output).unwrap();
+                                }
+                                if stream.write_all_buf(&mut output).await.is_err() {
+                                    return;
+                                }
+                                continue;
+                            }
+                        }
+                    }
+                    Err(_) => return,
+                };
+                match packet {
+                    Packet::Connect(_) => {
+                        ConnAck::new(ConnectReturnCode::Success, false)
+                            .write(&mut output)
+                            .unwrap();
+                    }
+                    Packet::Publish(publish) => {
+                        if publish.qos != QoS::AtMostOnce {
+                            PubAck::new(publish.pkid).write(&mut output).unwrap();
+                        }
+                        self.received.lock().unwrap().push(publish);
+                    }
+                    Packet::Subscribe(subscribe) => {
+                        let codes = subscribe
+                            .filters
+                            .iter()
+                            .map(|filter| SubscribeReasonCode::Success(filter.qos))
+                            .collect();
+                        filters.extend(subscribe.filters.into_iter().map(|filter| filter.path));
+                        SubAck::new(subscribe.pkid, codes)
+                            .write(&mut output)
+                            .unwrap();
+                    }
+                    Packet::PingReq => {
+                        PingResp.write(&mut output).unwrap();
+                    }
+                    Packet::Disconnect => return,
+                    _ => {}
+                }
+                if stream.write_all_buf(&mut output).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
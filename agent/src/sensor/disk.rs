@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chezmoi_database::metrics::entity::{Metric, MetricValue};
+use chezmoi_database::metrics::MetricHeader;
+use sysinfo::Disks;
+use tokio::time::Interval;
+
+use super::Collector;
+
+/// Size of the filesystem, in bytes, tagged with `mount`.
+pub const DISK_TOTAL: &str = "host.disk.total";
+/// Used space of the filesystem, in bytes, tagged with `mount`.
+pub const DISK_USED: &str = "host.disk.used";
+/// Used space of the filesystem, in percent, tagged with `mount`.
+pub const DISK_RATIO: &str = "host.disk.ratio";
+/// Bytes read per second, tagged with `device`.
+pub const DISK_READ_RATE: &str = "host.disk.read.rate";
+/// Bytes written per second, tagged with `device`.
+pub const DISK_WRITE_RATE: &str = "host.disk.write.rate";
+
+/// The kernel always counts sectors of 512 bytes in `/proc/diskstats`.
+const SECTOR_SIZE: u64 = 512;
+
+fn default_interval() -> u64 {
+    10
+}
+
+fn default_diskstats() -> PathBuf {
+    PathBuf::from("/proc/diskstats")
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    #[serde(default = "default_interval")]
+    interval: u64,
+    /// Mount points to monitor, all of them when empty.
+    #[serde(default)]
+    include: Vec<PathBuf>,
+    /// Mount points to ignore.
+    #[serde(default)]
+    exclude: Vec<PathBuf>,
+    #[serde(default = "default_diskstats")]
+    diskstats: PathBuf,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: default_interval(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            diskstats: default_diskstats(),
+        }
+    }
+}
+
+impl Config {
+    pub fn build(&self) -> Sensor {
+        Sensor {
+            disks: Disks::new(),
+            filter: Filter {
+                include: self.include.clone(),
+                exclude: self.exclude.clone(),
+            },
+            diskstats: self.diskstats.clone(),
+            diskstats_unavailable: false,
+            previous: HashMap::new(),
+            interval: tokio::time::interval(Duration::from_secs(self.interval)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Filter {
+    include: Vec<PathBuf>,
+    exclude: Vec<PathBuf>,
+}
+
+impl Filter {
+    fn accept(&self, mount: &std::path::Path) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|item| item == mount))
+            && !self.exclude.iter().any(|item| item == mount)
+    }
+}
+
+/// Bytes read and written by a device since boot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct DeviceStats {
+    read: u64,
+    written: u64,
+}
+
+/// Parses the content of `/proc/diskstats`, indexed by device name.
+fn parse_diskstats(content: &str) -> HashMap<String, DeviceStats> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().skip(2);
+            let name = fields.next()?;
+            // reads completed, reads merged, sectors read
+            let read = fields.nth(2)?.parse::<u64>().ok()?;
+            // time reading, writes completed, writes merged, sectors written
+            let written = fields.nth(3)?.parse::<u64>().ok()?;
+            Some((
+                name.to_string(),
+                DeviceStats {
+                    read: read * SECTOR_SIZE,
+                    written: written * SECTOR_SIZE,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Name of the device in `/proc/diskstats`, following the links like `/dev/mapper/*` or `/dev/root`.
+fn device_name(disk: &sysinfo::Disk) -> Option<String> {
+    let path = PathBuf::from(disk.name());
+    let path = std::fs::canonicalize(&path).unwrap_or(path);
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+#[derive(Debug)]
+pub(crate) struct Sensor {
+    disks: Disks,
+    filter: Filter,
+    diskstats: PathBuf,
+    /// Set once the missing file has been reported, to avoid a warning on every tick.
+    diskstats_unavailable: bool,
+    /// Last values read for each device, to compute the rates.
+    previous: HashMap<String, (Instant, DeviceStats)>,
+    interval: Interval,
+}
+
+impl Sensor {
+    fn disks(&self) -> impl Iterator<Item = &sysinfo::Disk> {
+        self.disks
+            .list()
+            .iter()
+            .filter(|disk| self.filter.accept(disk.mount_point()))
+    }
+
+    fn collect_usage(&self, now: u64, buffer: &mut Collector) {
+        for disk in self.disks() {
+            let total = disk.total_space() as f64;
+            let used = total - disk.available_space() as f64;
+            let mount = disk.mount_point().to_string_lossy().to_string();
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(DISK_TOTAL).with_tag("mount", mount.clone()),
+                value: MetricValue::gauge(total),
+            });
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(DISK_USED).with_tag("mount", mount.clone()),
+                value: MetricValue::gauge(used),
+            });
+            if total > 0.0 {
+                buffer.collect(Metric {
+                    timestamp: now,
+                    header: MetricHeader::new(DISK_RATIO).with_tag("mount", mount),
+                    value: MetricValue::gauge(used * 100.0 / total),
+                });
+            }
+        }
+    }
+
+    /// Without `/proc/diskstats`, the I/O rates are skipped and only the usage is collected.
+    async fn read_diskstats(&mut self) -> Option<String> {
+        match tokio::fs::read_to_string(&self.diskstats).await {
+            Ok(content) => {
+                self.diskstats_unavailable = false;
+                Some(content)
+            }
+            Err(error) => {
+                if !self.diskstats_unavailable {
+                    tracing::warn!(
+                        message = "unable to read disk stats, skipping io rates",
+                        path = %self.diskstats.display(),
+                        cause = %error
+                    );
+                    self.diskstats_unavailable = true;
+                }
+                None
+            }
+        }
+    }
+
+    async fn collect_io(&mut self, now: u64, buffer: &mut Collector) {
+        let Some(content) = self.read_diskstats().await else {
+            return;
+        };
+        let stats = parse_diskstats(&content);
+        let instant = Instant::now();
+        let mut devices = self.disks().filter_map(device_name).collect::<Vec<_>>();
+        devices.sort();
+        devices.dedup();
+        for device in devices {
+            let Some(current) = stats.get(&device).copied() else {
+                continue;
+            };
+            let previous = self.previous.insert(device.clone(), (instant, current));
+            let Some((since, previous)) = previous else {
+                continue;
+            };
+            let elapsed = instant.duration_since(since).as_secs_f64();
+            // the counters are reset when the device is reattached
+            if elapsed <= 0.0 || current.read < previous.read || current.written < previous.written
+            {
+                continue;
+            }
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(DISK_READ_RATE).with_tag("device", device.clone()),
+                value: MetricValue::gauge((current.read - previous.read) as f64 / elapsed),
+            });
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(DISK_WRITE_RATE).with_tag("device", device),
+                value: MetricValue::gauge((current.written - previous.written) as f64 / elapsed),
+            });
+        }
+    }
+
+    async fn iterate(&mut self, buffer: &mut Collector) -> anyhow::Result<()> {
+        // the list is refreshed to catch the newly mounted filesystems
+        self.disks.refresh_list();
+        let now = chezmoi_database::helper::now();
+        self.collect_usage(now, buffer);
+        self.collect_io(now, buffer).await;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "disk", skip_all)]
+    pub async fn run(mut self, context: super::Context) -> anyhow::Result<()> {
+        let mut collector = super::Collector::new(super::Cache::default(), 20);
+        while context.state.is_running() {
+            self.interval.tick().await;
+            if let Err(error) = self.iterate(&mut collector).await {
+                tracing::error!(message = "unable to collect metrics", cause = %error);
+            }
+
+            context.send_all(collector.flush()).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn should_parse_diskstats() {
+        let content = r#"   7       0 loop0 48 0 2144 12 0 0 0 0 0 36 12 0 0 0 0 0 0
+ 179       0 mmcblk0 12005 3869 1041898 46791 46110 48066 2853688 1173880 0 317680 1223826 0 0 0 0 1549 3154
+ 179       2 mmcblk0p2 11848 3869 1033786 46616 46110 48066 2853688 1173880 0 317528 1220496 0 0 0 0 0 0
+"#;
+        let stats = super::parse_diskstats(content);
+        assert_eq!(stats.len(), 3);
+        assert_eq!(
+            stats.get("mmcblk0p2"),
+            Some(&super::DeviceStats {
+                read: 1033786 * 512,
+                written: 2853688 * 512,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn should_skip_io_without_diskstats() {
+        let config = super::Config {
+            diskstats: PathBuf::from("/nonexistent/diskstats"),
+            ..Default::default()
+        };
+        let mut sensor = config.build();
+        let mut collector = crate::sensor::Collector::new(crate::sensor::Cache::default(), 20);
+        assert!(sensor.iterate(&mut collector).await.is_ok());
+        assert!(sensor.diskstats_unavailable);
+        assert!(sensor.iterate(&mut collector).await.is_ok());
+        assert!(collector
+            .flush()
+            .unwrap_or_default()
+            .iter()
+            .all(|metric| metric.header.name != super::DISK_READ_RATE));
+    }
+
+    #[test]
+    fn should_filter_mount_points() {
+        let filter = super::Filter::default();
+        assert!(filter.accept(Path::new("/")));
+
+        let filter = super::Filter {
+            include: Vec::new(),
+            exclude: vec![PathBuf::from("/boot/firmware")],
+        };
+        assert!(filter.accept(Path::new("/")));
+        assert!(!filter.accept(Path::new("/boot/firmware")));
+
+        let filter = super::Filter {
+            include: vec![PathBuf::from("/"), PathBuf::from("/data")],
+            exclude: vec![PathBuf::from("/data")],
+        };
+        assert!(filter.accept(Path::new("/")));
+        assert!(!filter.accept(Path::new("/data")));
+        assert!(!filter.accept(Path::new("/boot")));
+    }
+}
@@ -0,0 +1,313 @@
+use std::borrow::Cow;
+use std::process::Stdio;
+use std::time::Duration;
+
+use chezmoi_database::metrics::entity::{Metric, MetricValue};
+use chezmoi_database::metrics::{MetricHeader, MetricTags};
+use indexmap::IndexMap;
+
+use super::value::{parse_json, parse_text, ValueType};
+use super::Collector;
+
+fn default_interval() -> u64 {
+    60
+}
+
+fn default_timeout() -> u64 {
+    10
+}
+
+/// How the standard output of a command is read.
+#[derive(Clone, Copy, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Format {
+    /// The whole output is a single value, reported under the command name.
+    #[default]
+    Number,
+    /// One `key=value`, or `key: value`, per line, reported as `{name}.{key}`.
+    KeyValue,
+    /// The numbers and booleans of a JSON document, reported as `{name}.{path}`.
+    Json,
+}
+
+/// Command to run periodically, executed with `sh -c`.
+///
+/// ```toml
+/// [[agent.exec.commands]]
+/// command = "upsc ups@localhost"
+/// format = "key-value"
+/// name = "ups"
+/// fields = ["battery.charge", "input.voltage", "ups.load"]
+/// tags = { device = "ups" }
+/// ```
+#[derive(Clone, Debug, serde::Deserialize)]
+pub(crate) struct Command {
+    command: String,
+    /// Metric name, or prefix when the output contains several values.
+    name: String,
+    #[serde(default)]
+    tags: IndexMap<String, String>,
+    #[serde(default)]
+    format: Format,
+    /// Keys, or JSON paths, to report. All the values are reported when empty.
+    #[serde(default)]
+    fields: Vec<String>,
+    #[serde(default, rename = "type")]
+    kind: ValueType,
+    #[serde(default = "default_interval")]
+    interval: u64,
+    /// Time after which the command gets killed, in seconds.
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+}
+
+/// Collects the leaves of a JSON document with their dot separated path.
+fn flatten_json<'a>(
+    prefix: String,
+    value: &'a serde_json::Value,
+    buffer: &mut Vec<(String, &'a serde_json::Value)>,
+) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_json(path, value, buffer);
+            }
+        }
+        serde_json::Value::Array(_) | serde_json::Value::Null => {}
+        _ => buffer.push((prefix, value)),
+    }
+}
+
+impl Command {
+    fn accept(&self, key: &str) -> bool {
+        self.fields.is_empty() || self.fields.iter().any(|field| field == key)
+    }
+
+    fn metric_name(&self, key: &str) -> String {
+        if key.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{key}", self.name)
+        }
+    }
+
+    /// Reads the values from the command output, with their name.
+    fn parse(&self, output: &str) -> Vec<(String, MetricValue)> {
+        match self.format {
+            Format::Number => parse_text(output, self.kind)
+                .map(|value| vec![(self.name.clone(), value)])
+                .unwrap_or_default(),
+            Format::KeyValue => output
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .filter_map(|line| line.split_once('=').or_else(|| line.split_once(':')))
+                .map(|(key, value)| (key.trim(), value))
+                .filter(|(key, _)| self.accept(key))
+                .filter_map(|(key, value)| {
+                    parse_text(value, self.kind).map(|value| (self.metric_name(key), value))
+                })
+                .collect(),
+            Format::Json => {
+                let Ok(root) = serde_json::from_str::<serde_json::Value>(output) else {
+                    return Vec::new();
+                };
+                let mut leaves = Vec::new();
+                flatten_json(String::new(), &root, &mut leaves);
+                leaves
+                    .into_iter()
+                    .filter(|(key, _)| self.accept(key))
+                    .filter_map(|(key, value)| {
+                        parse_json(value, self.kind).map(|value| (self.metric_name(&key), value))
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn tags(&self) -> MetricTags {
+        self.tags
+            .iter()
+            .fold(MetricTags::default(), |tags, (key, value)| {
+                tags.with(Cow::Owned(key.clone()), value.clone())
+            })
+    }
+
+    async fn execute(&self) -> anyhow::Result<String> {
+        let child = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let output =
+            tokio::time::timeout(Duration::from_secs(self.timeout), child.wait_with_output())
+                .await
+                .map_err(|_| anyhow::anyhow!("command timed out after {}s", self.timeout))??;
+        if !output.status.success() {
+            anyhow::bail!(
+                "command exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    async fn iterate(&self, buffer: &mut Collector) -> anyhow::Result<()> {
+        let output = self.execute().await?;
+        let now = chezmoi_database::helper::now();
+        let values = self.parse(&output);
+        if values.is_empty() {
+            tracing::warn!(message = "no value found in output", name = self.name);
+        }
+        let tags = self.tags();
+        for (name, value) in values {
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::from((name, tags.clone())),
+                value,
+            });
+        }
+        Ok(())
+    }
+
+    async fn run(&self, context: super::Context) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.interval));
+        let mut collector = super::Collector::new(super::Cache::default(), 10);
+        while context.state.is_running() {
+            interval.tick().await;
+            if let Err(error) = self.iterate(&mut collector).await {
+                tracing::error!(message = "unable to collect metrics", name = self.name, cause = %error);
+            }
+
+            context.send_all(collector.flush()).await;
+        }
+    }
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct Config {
+    #[serde(default)]
+    commands: Vec<Command>,
+}
+
+impl Config {
+    pub fn build(&self) -> Sensor {
+        Sensor {
+            commands: self.commands.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Sensor {
+    commands: Vec<Command>,
+}
+
+impl Sensor {
+    #[tracing::instrument(name = "exec", skip_all)]
+    pub async fn run(self, context: super::Context) -> anyhow::Result<()> {
+        // each command has its own interval
+        futures::future::join_all(
+            self.commands
+                .iter()
+                .map(|command| command.run(context.clone())),
+        )
+        .await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chezmoi_database::metrics::entity::MetricValue;
+
+    fn command(config: &str) -> super::Command {
+        toml::from_str(config).unwrap()
+    }
+
+    #[test]
+    fn should_parse_number() {
+        let cmd = command(
+            r#"command = "echo 42"
+name = "answer""#,
+        );
+        assert_eq!(
+            cmd.parse("42.5\n"),
+            vec![("answer".to_string(), MetricValue::gauge(42.5))]
+        );
+        assert!(cmd.parse("not a number").is_empty());
+    }
+
+    #[test]
+    fn should_parse_key_values() {
+        let cmd = command(
+            r#"command = "upsc ups"
+name = "ups"
+format = "key-value"
+fields = ["battery.charge", "ups.load", "ups.status"]"#,
+        );
+        let output = "battery.charge: 100\nbattery.runtime: 1800\nups.load=12\nups.status: OL\n";
+        assert_eq!(
+            cmd.parse(output),
+            vec![
+                ("ups.battery.charge".to_string(), MetricValue::gauge(100.0)),
+                ("ups.ups.load".to_string(), MetricValue::gauge(12.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_json() {
+        let cmd = command(
+            r#"command = "router-stats"
+name = "router"
+format = "json"
+type = "count""#,
+        );
+        let output =
+            r#"{"wan": {"rx": 1200, "tx": 300, "name": "eth1"}, "clients": [1, 2], "up": true}"#;
+        let mut values = cmd.parse(output);
+        values.sort_by(|first, second| first.0.cmp(&second.0));
+        assert_eq!(
+            values,
+            vec![
+                ("router.up".to_string(), MetricValue::count(1)),
+                ("router.wan.rx".to_string(), MetricValue::count(1200)),
+                ("router.wan.tx".to_string(), MetricValue::count(300)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_fail_on_error_or_timeout() {
+        let cmd = command(
+            r#"command = "echo 12"
+name = "value""#,
+        );
+        assert_eq!(cmd.execute().await.unwrap(), "12\n");
+
+        let cmd = command(
+            r#"command = "echo oops >&2; exit 3"
+name = "value""#,
+        );
+        let error = cmd.execute().await.unwrap_err();
+        assert!(error.to_string().contains("oops"), "{error}");
+
+        let cmd = command(
+            r#"command = "sleep 5"
+name = "value"
+timeout = 1"#,
+        );
+        let error = cmd.execute().await.unwrap_err();
+        assert!(error.to_string().contains("timed out"), "{error}");
+    }
+}
@@ -0,0 +1,285 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use chezmoi_database::metrics::entity::{Metric, MetricValue};
+use chezmoi_database::metrics::MetricHeader;
+use tokio::time::Interval;
+
+use super::Collector;
+
+/// Time to receive the response headers, in milliseconds, tagged with `target`.
+pub const HTTP_PROBE_RESPONSE_TIME: &str = "probe.http.response_time";
+/// Status code of the response, as a gauge, tagged with `target`.
+pub const HTTP_PROBE_STATUS: &str = "probe.http.status";
+/// Whether the target answered with the expected status, tagged with `target`.
+pub const HTTP_PROBE_UP: &str = "probe.http.up";
+/// Days before the certificate of the target expires, tagged with `target`.
+pub const HTTP_PROBE_CERTIFICATE_EXPIRY: &str = "probe.http.certificate.expiry";
+
+const ONE_DAY: f64 = 60.0 * 60.0 * 24.0;
+
+fn default_interval() -> u64 {
+    60
+}
+
+fn default_timeout() -> u64 {
+    10
+}
+
+/// URL to request periodically.
+///
+/// ```toml
+/// [[agent.http_probe.targets]]
+/// name = "nextcloud"
+/// url = "https://cloud.example.com/status.php"
+///
+/// [[agent.http_probe.targets]]
+/// name = "router"
+/// url = "http://192.168.1.1/"
+/// status = 401
+/// ```
+#[derive(Clone, Debug, serde::Deserialize)]
+pub(crate) struct Target {
+    /// Used as the `target` tag.
+    name: String,
+    url: String,
+    /// Status code expected from the target, any success or redirection when not provided.
+    #[serde(default)]
+    status: Option<u16>,
+}
+
+impl Target {
+    fn is_up(&self, status: reqwest::StatusCode) -> bool {
+        match self.status {
+            Some(expected) => status.as_u16() == expected,
+            None => status.is_success() || status.is_redirection(),
+        }
+    }
+
+    fn header(&self, name: &'static str) -> MetricHeader {
+        MetricHeader::new(name).with_tag("target", self.name.clone())
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    #[serde(default = "default_interval")]
+    interval: u64,
+    /// Time after which a target is considered down, in seconds.
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+    #[serde(default)]
+    targets: Vec<Target>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: default_interval(),
+            timeout: default_timeout(),
+            targets: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn build(&self) -> anyhow::Result<Sensor> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(self.timeout))
+            .tls_info(true)
+            .build()
+            .context("building http client")?;
+        Ok(Sensor {
+            client,
+            targets: self.targets.clone(),
+            interval: tokio::time::interval(Duration::from_secs(self.interval)),
+        })
+    }
+}
+
+/// Result of a single request to a target.
+#[derive(Debug, Default, PartialEq)]
+struct Probe {
+    /// Unknown when no response was received.
+    status: Option<u16>,
+    /// In milliseconds.
+    response_time: Option<f64>,
+    up: bool,
+    /// In days, only known for HTTPS targets.
+    certificate_expiry: Option<f64>,
+}
+
+/// Reads the number of days before the `notAfter` date of a DER encoded certificate.
+fn certificate_expiry(der: &[u8], now: u64) -> Option<f64> {
+    let (_, certificate) = x509_parser::parse_x509_certificate(der).ok()?;
+    let not_after = certificate.validity().not_after.timestamp();
+    Some((not_after - now as i64) as f64 / ONE_DAY)
+}
+
+#[derive(Debug)]
+pub(crate) struct Sensor {
+    client: reqwest::Client,
+    targets: Vec<Target>,
+    interval: Interval,
+}
+
+impl Sensor {
+    async fn probe(&self, target: &Target) -> Probe {
+        let start = Instant::now();
+        let response = match self.client.get(&target.url).send().await {
+            Ok(response) => response,
+            Err(error) => {
+                tracing::debug!(message = "target unreachable", target = target.name, cause = %error);
+                return Probe::default();
+            }
+        };
+        let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+        let certificate_expiry = response
+            .extensions()
+            .get::<reqwest::tls::TlsInfo>()
+            .and_then(|info| info.peer_certificate())
+            .and_then(|der| certificate_expiry(der, chezmoi_database::helper::now()));
+        Probe {
+            status: Some(response.status().as_u16()),
+            response_time: Some(elapsed),
+            up: target.is_up(response.status()),
+            certificate_expiry,
+        }
+    }
+
+    async fn iterate(&mut self, buffer: &mut Collector) -> anyhow::Result<()> {
+        let probes =
+            futures::future::join_all(self.targets.iter().map(|target| self.probe(target))).await;
+        let now = chezmoi_database::helper::now();
+        for (target, probe) in self.targets.iter().zip(probes) {
+            buffer.collect(Metric {
+                timestamp: now,
+                header: target.header(HTTP_PROBE_UP),
+                value: MetricValue::bool(probe.up),
+            });
+            if let Some(status) = probe.status {
+                buffer.collect(Metric {
+                    timestamp: now,
+                    header: target.header(HTTP_PROBE_STATUS),
+                    value: MetricValue::gauge(status as f64),
+                });
+            }
+            if let Some(response_time) = probe.response_time {
+                buffer.collect(Metric {
+                    timestamp: now,
+                    header: target.header(HTTP_PROBE_RESPONSE_TIME),
+                    value: MetricValue::gauge(response_time),
+                });
+            }
+            if let Some(expiry) = probe.certificate_expiry {
+                buffer.collect(Metric {
+                    timestamp: now,
+                    header: target.header(HTTP_PROBE_CERTIFICATE_EXPIRY),
+                    value: MetricValue::gauge(expiry),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "http_probe", skip_all)]
+    pub async fn run(mut self, context: super::Context) -> anyhow::Result<()> {
+        let mut collector = super::Collector::new(super::Cache::default(), self.targets.len() * 4);
+        while context.state.is_running() {
+            self.interval.tick().await;
+            if let Err(error) = self.iterate(&mut collector).await {
+                tracing::error!(message = "unable to collect metrics", cause = %error);
+            }
+
+            context.send_all(collector.flush()).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use chezmoi_database::metrics::entity::MetricValue;
+
+    async fn start_stub() -> std::net::SocketAddr {
+        let app = axum::Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route(
+                "/error",
+                get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            )
+            .route("/login", get(|| async { StatusCode::UNAUTHORIZED }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        address
+    }
+
+    fn target(name: &str, url: String, status: Option<u16>) -> super::Target {
+        super::Target {
+            name: name.into(),
+            url,
+            status,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_probe_targets() {
+        let address = start_stub().await;
+        // a port that was free a moment ago
+        let closed = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap()
+        };
+        let config: super::Config = toml::from_str("timeout = 2").unwrap();
+        let sensor = config.build().unwrap();
+
+        let probe = sensor
+            .probe(&target("ok", format!("http://{address}/ok"), None))
+            .await;
+        assert_eq!(probe.status, Some(200));
+        assert!(probe.up);
+        assert!(probe.response_time.is_some());
+        assert!(probe.certificate_expiry.is_none());
+
+        let probe = sensor
+            .probe(&target("error", format!("http://{address}/error"), None))
+            .await;
+        assert_eq!(probe.status, Some(500));
+        assert!(!probe.up);
+
+        let probe = sensor
+            .probe(&target(
+                "login",
+                format!("http://{address}/login"),
+                Some(401),
+            ))
+            .await;
+        assert_eq!(probe.status, Some(401));
+        assert!(probe.up);
+
+        let probe = sensor
+            .probe(&target("closed", format!("http://{closed}/"), None))
+            .await;
+        assert_eq!(probe, super::Probe::default());
+    }
+
+    #[tokio::test]
+    async fn should_report_status_as_gauge() {
+        let address = start_stub().await;
+        let config: super::Config = toml::from_str("timeout = 2").unwrap();
+        let mut sensor = config.build().unwrap();
+        sensor.targets = vec![target("ok", format!("http://{address}/ok"), None)];
+
+        let mut collector = crate::sensor::Collector::new(Default::default(), 4);
+        sensor.iterate(&mut collector).await.unwrap();
+        let metrics = collector.flush().unwrap();
+        let status = metrics
+            .iter()
+            .find(|metric| metric.header.name == super::HTTP_PROBE_STATUS)
+            .unwrap();
+        assert_eq!(status.value, MetricValue::gauge(200.0));
+    }
+}
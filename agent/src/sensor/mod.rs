@@ -17,9 +17,20 @@ const fn one_hour() -> u64 {
 pub mod atc_thermometer;
 #[cfg(feature = "sensor-bt-scanner")]
 pub mod bt_scanner;
+pub mod disk;
+pub mod exec;
+#[cfg(feature = "sensor-http-probe")]
+pub mod http_probe;
 #[cfg(feature = "sensor-miflora")]
 pub mod miflora;
+#[cfg(feature = "sensor-mqtt")]
+pub mod mqtt;
+pub mod network;
+pub mod process;
+pub mod reachability;
 pub mod system;
+pub mod thermal;
+pub(crate) mod value;
 
 #[derive(Debug, Default, serde::Deserialize)]
 pub(crate) struct ConfigWrapper<C> {
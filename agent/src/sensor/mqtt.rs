@@ -0,0 +1,270 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chezmoi_database::metrics::entity::{Metric, MetricValue};
+use chezmoi_database::metrics::{MetricHeader, MetricTags};
+use indexmap::IndexMap;
+use rumqttc::{AsyncClient, Event, EventLoop, Packet, Publish, QoS};
+
+use super::value::{parse_json, parse_text, ValueType};
+use super::Collector;
+use crate::mqtt::BrokerConfig;
+
+const DEFAULT_CLIENT_ID: &str = "chezmoi-agent-sensor";
+
+/// Delay before polling the broker connection again after a failure.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Maps the messages received on a topic to a metric.
+///
+/// The name and the tag values can refer to the levels of the topic, `{1}` being
+/// replaced by `living_room` for a message received on `zigbee2mqtt/living_room`.
+///
+/// ```toml
+/// [[agent.mqtt.subscriptions]]
+/// topic = "zigbee2mqtt/+"
+/// field = "temperature"
+/// name = "zigbee.temperature"
+/// tags = { device = "{1}" }
+/// ```
+#[derive(Clone, Debug, serde::Deserialize)]
+pub(crate) struct Subscription {
+    /// Topic filter, supporting the `+` and `#` wildcards.
+    topic: String,
+    name: String,
+    #[serde(default)]
+    tags: IndexMap<String, String>,
+    /// Path of the value in a JSON payload, like `sensor.temperature`.
+    /// When not provided, the whole payload is expected to be the value.
+    #[serde(default)]
+    field: Option<String>,
+    #[serde(default, rename = "type")]
+    kind: ValueType,
+}
+
+/// Replaces the `{index}` placeholders with the matching level of the topic.
+fn render(template: &str, levels: &[&str]) -> String {
+    let mut res = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        res.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let key = &rest[start + 1..start + end];
+        match key.parse::<usize>().ok().and_then(|idx| levels.get(idx)) {
+            Some(level) => res.push_str(level),
+            None => res.push_str(&rest[start..=start + end]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    res.push_str(rest);
+    res
+}
+
+impl Subscription {
+    fn value(&self, payload: &[u8]) -> Option<MetricValue> {
+        match self.field {
+            Some(ref field) => {
+                let root: serde_json::Value = serde_json::from_slice(payload).ok()?;
+                let value = field
+                    .split('.')
+                    .try_fold(&root, |value, key| value.get(key))?;
+                parse_json(value, self.kind)
+            }
+            None => parse_text(std::str::from_utf8(payload).ok()?, self.kind),
+        }
+    }
+
+    /// Builds the metric from a received message, `None` when it doesn't match.
+    fn metric(&self, timestamp: u64, message: &Publish) -> Option<Metric> {
+        if !rumqttc::matches(&message.topic, &self.topic) {
+            return None;
+        }
+        let Some(value) = self.value(&message.payload) else {
+            tracing::debug!(
+                message = "unable to read value",
+                topic = message.topic,
+                name = self.name
+            );
+            return None;
+        };
+        let levels = message.topic.split('/').collect::<Vec<_>>();
+        let tags = self
+            .tags
+            .iter()
+            .fold(MetricTags::default(), |tags, (key, value)| {
+                tags.with(Cow::Owned(key.clone()), render(value, &levels))
+            });
+        Some(Metric {
+            timestamp,
+            header: MetricHeader::from((render(&self.name, &levels), tags)),
+            value,
+        })
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    #[serde(flatten)]
+    broker: BrokerConfig,
+    #[serde(default)]
+    subscriptions: Vec<Subscription>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            broker: BrokerConfig::new("localhost", 1883),
+            subscriptions: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn build(&self) -> Sensor {
+        let filters = self
+            .subscriptions
+            .iter()
+            .map(|sub| sub.topic.clone())
+            .collect::<HashSet<_>>();
+        let (client, eventloop) =
+            AsyncClient::new(self.broker.options(DEFAULT_CLIENT_ID), filters.len() + 10);
+        Sensor {
+            client,
+            eventloop,
+            filters,
+            subscriptions: self.subscriptions.clone(),
+        }
+    }
+}
+
+pub(crate) struct Sensor {
+    client: AsyncClient,
+    eventloop: EventLoop,
+    filters: HashSet<String>,
+    subscriptions: Vec<Subscription>,
+}
+
+impl std::fmt::Debug for Sensor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sensor")
+            .field("filters", &self.filters)
+            .field("subscriptions", &self.subscriptions)
+            .finish()
+    }
+}
+
+impl Sensor {
+    fn subscribe(&self) {
+        for filter in self.filters.iter() {
+            if let Err(error) = self.client.try_subscribe(filter, QoS::AtMostOnce) {
+                tracing::error!(message = "unable to subscribe", topic = filter, cause = %error);
+            }
+        }
+    }
+
+    fn handle(&self, message: &Publish, buffer: &mut Collector) {
+        let now = chezmoi_database::helper::now();
+        self.subscriptions
+            .iter()
+            .filter_map(|sub| sub.metric(now, message))
+            .for_each(|metric| buffer.collect(metric));
+    }
+
+    #[tracing::instrument(name = "mqtt", skip_all)]
+    pub async fn run(mut self, context: super::Context) -> anyhow::Result<()> {
+        let mut collector = super::Collector::new(super::Cache::default(), 10);
+        while context.state.is_running() {
+            match self.eventloop.poll().await {
+                // subscriptions are lost when reconnecting with a clean session
+                Ok(Event::Incoming(Packet::ConnAck(_))) => self.subscribe(),
+                Ok(Event::Incoming(Packet::Publish(message))) => {
+                    self.handle(&message, &mut collector);
+                    context.send_all(collector.flush()).await;
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    tracing::warn!(message = "mqtt connection failed", cause = %error);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chezmoi_database::metrics::entity::MetricValue;
+    use chezmoi_database::metrics::MetricHeader;
+
+    use crate::mqtt::broker::Broker;
+
+    #[test]
+    fn should_render_topic_levels() {
+        assert_eq!(
+            super::render("zigbee.{1}.{2}", &["zigbee2mqtt", "kitchen", "plug"]),
+            "zigbee.kitchen.plug"
+        );
+        assert_eq!(super::render("{5}-{a}", &["foo"]), "{5}-{a}");
+    }
+
+    #[tokio::test]
+    async fn should_collect_subscribed_values() {
+        let broker = Broker::start().await;
+        let config: super::Config = toml::from_str(&format!(
+            r#"
+host = "127.0.0.1"
+port = {}
+
+[[subscriptions]]
+topic = "zigbee2mqtt/+"
+field = "sensor.temperature"
+name = "zigbee.temperature"
+tags = {{ device = "{{1}}" }}
+
+[[subscriptions]]
+topic = "home/door"
+name = "home.door"
+type = "bool"
+"#,
+            broker.address.port()
+        ))
+        .unwrap();
+
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(10);
+        let context = crate::sensor::Context::new(true, sender);
+        tokio::spawn(config.build().run(context.clone()));
+
+        let mut metrics = Vec::new();
+        while metrics.len() < 2 {
+            broker.publish("zigbee2mqtt/kitchen", r#"{"sensor":{"temperature":21.5}}"#);
+            broker.publish("home/door", "ON");
+            if let Ok(Some(batch)) =
+                tokio::time::timeout(Duration::from_millis(50), receiver.recv()).await
+            {
+                metrics.extend(batch);
+            }
+        }
+        context.state.stop();
+
+        let temperature = metrics
+            .iter()
+            .find(|metric| metric.header.name == "zigbee.temperature")
+            .unwrap();
+        assert_eq!(
+            temperature.header,
+            MetricHeader::new("zigbee.temperature").with_tag("device", "kitchen")
+        );
+        assert_eq!(temperature.value, MetricValue::gauge(21.5));
+        let door = metrics
+            .iter()
+            .find(|metric| metric.header.name == "home.door")
+            .unwrap();
+        assert_eq!(door.value, MetricValue::bool(true));
+    }
+}
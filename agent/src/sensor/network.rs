@@ -0,0 +1,152 @@
+use std::time::{Duration, Instant};
+
+use chezmoi_database::metrics::entity::{Metric, MetricValue};
+use chezmoi_database::metrics::MetricHeader;
+use sysinfo::Networks;
+use tokio::time::Interval;
+
+use super::Collector;
+
+/// Bytes received since boot, tagged with `interface`.
+pub const NETWORK_RECEIVED: &str = "host.network.received";
+/// Bytes transmitted since boot, tagged with `interface`.
+pub const NETWORK_TRANSMITTED: &str = "host.network.transmitted";
+/// Bytes received per second, tagged with `interface`.
+pub const NETWORK_RECEIVED_RATE: &str = "host.network.received.rate";
+/// Bytes transmitted per second, tagged with `interface`.
+pub const NETWORK_TRANSMITTED_RATE: &str = "host.network.transmitted.rate";
+
+fn default_interval() -> u64 {
+    10
+}
+
+fn default_exclude() -> Vec<String> {
+    vec![String::from("lo")]
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    #[serde(default = "default_interval")]
+    interval: u64,
+    /// Interfaces to monitor, all of them when empty.
+    #[serde(default)]
+    include: Vec<String>,
+    /// Interfaces to ignore, the loopback by default.
+    #[serde(default = "default_exclude")]
+    exclude: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: default_interval(),
+            include: Vec::new(),
+            exclude: default_exclude(),
+        }
+    }
+}
+
+impl Config {
+    pub fn build(&self) -> Sensor {
+        Sensor {
+            inner: Networks::new(),
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+            last_refresh: None,
+            interval: tokio::time::interval(Duration::from_secs(self.interval)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Sensor {
+    inner: Networks,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    /// Moment of the previous refresh, the rates are only known after the second one.
+    last_refresh: Option<Instant>,
+    interval: Interval,
+}
+
+impl Sensor {
+    fn accept(&self, interface: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|item| item == interface))
+            && !self.exclude.iter().any(|item| item == interface)
+    }
+
+    async fn iterate(&mut self, buffer: &mut Collector) -> anyhow::Result<()> {
+        // the list is refreshed to catch the interfaces coming up
+        self.inner.refresh_list();
+        let instant = Instant::now();
+        let elapsed = self
+            .last_refresh
+            .replace(instant)
+            .map(|previous| instant.duration_since(previous).as_secs_f64())
+            .filter(|elapsed| *elapsed > 0.0);
+        let now = chezmoi_database::helper::now();
+        let mut interfaces = self
+            .inner
+            .list()
+            .iter()
+            .filter(|(name, _)| self.accept(name))
+            .collect::<Vec<_>>();
+        interfaces.sort_by_key(|(name, _)| *name);
+        for (name, data) in interfaces {
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(NETWORK_RECEIVED).with_tag("interface", name.clone()),
+                value: MetricValue::count(data.total_received()),
+            });
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(NETWORK_TRANSMITTED).with_tag("interface", name.clone()),
+                value: MetricValue::count(data.total_transmitted()),
+            });
+            if let Some(elapsed) = elapsed {
+                buffer.collect(Metric {
+                    timestamp: now,
+                    header: MetricHeader::new(NETWORK_RECEIVED_RATE)
+                        .with_tag("interface", name.clone()),
+                    value: MetricValue::gauge(data.received() as f64 / elapsed),
+                });
+                buffer.collect(Metric {
+                    timestamp: now,
+                    header: MetricHeader::new(NETWORK_TRANSMITTED_RATE)
+                        .with_tag("interface", name.clone()),
+                    value: MetricValue::gauge(data.transmitted() as f64 / elapsed),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "network", skip_all)]
+    pub async fn run(mut self, context: super::Context) -> anyhow::Result<()> {
+        let mut collector = super::Collector::new(super::Cache::default(), 20);
+        while context.state.is_running() {
+            self.interval.tick().await;
+            if let Err(error) = self.iterate(&mut collector).await {
+                tracing::error!(message = "unable to collect metrics", cause = %error);
+            }
+
+            context.send_all(collector.flush()).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[tokio::test]
+    async fn should_filter_interfaces() {
+        let sensor = super::Config::default().build();
+        assert!(sensor.accept("eth0"));
+        assert!(!sensor.accept("lo"));
+
+        let config: super::Config = toml::from_str(r#"include = ["eth0", "lo"]"#).unwrap();
+        let sensor = config.build();
+        assert!(sensor.accept("eth0"));
+        assert!(!sensor.accept("lo"));
+        assert!(!sensor.accept("wlan0"));
+    }
+}
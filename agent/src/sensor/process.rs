@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use chezmoi_database::metrics::entity::{Metric, MetricValue};
+use chezmoi_database::metrics::MetricHeader;
+use sysinfo::{ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, UpdateKind};
+use tokio::time::Interval;
+
+use super::Collector;
+
+/// Whether a watched process is running, tagged with `process`.
+pub const PROCESS_RUNNING: &str = "host.process.running";
+/// CPU usage of a watched process, in percent of a core, tagged with `process`.
+pub const PROCESS_CPU_USAGE: &str = "host.process.cpu.usage";
+/// Resident memory of a watched process, in bytes, tagged with `process`.
+pub const PROCESS_MEMORY: &str = "host.process.memory";
+/// Number of times a watched process got a new PID since the agent started, tagged with `process`.
+pub const PROCESS_RESTARTS: &str = "host.process.restarts";
+
+fn default_interval() -> u64 {
+    10
+}
+
+/// Process to look for.
+///
+/// ```toml
+/// [[agent.process.watch]]
+/// name = "mosquitto"
+///
+/// [[agent.process.watch]]
+/// name = "home-assistant"
+/// cmdline = "homeassistant --config"
+/// ```
+#[derive(Clone, Debug, serde::Deserialize)]
+pub(crate) struct Watch {
+    /// Used as the `process` tag, and to match the process name when no `cmdline` is provided.
+    name: String,
+    /// Text to look for in the command line, with the arguments separated by spaces.
+    #[serde(default)]
+    cmdline: Option<String>,
+}
+
+impl Watch {
+    fn matches(&self, name: &str, cmdline: &str) -> bool {
+        match self.cmdline {
+            Some(ref pattern) => cmdline.contains(pattern.as_str()),
+            None => self.name == name,
+        }
+    }
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct Config {
+    #[serde(default = "default_interval")]
+    interval: u64,
+    #[serde(default)]
+    watch: Vec<Watch>,
+}
+
+impl Config {
+    pub fn build(&self) -> Sensor {
+        Sensor {
+            inner: sysinfo::System::new(),
+            watched: self
+                .watch
+                .iter()
+                .cloned()
+                .map(|watch| Watched {
+                    watch,
+                    state: State::default(),
+                })
+                .collect(),
+            interval: tokio::time::interval(Duration::from_secs(self.interval)),
+        }
+    }
+}
+
+/// Keeps track of the PID of a watched process to detect the restarts.
+#[derive(Debug, Default)]
+struct State {
+    pid: Option<u32>,
+    restarts: u64,
+}
+
+impl State {
+    /// Counts a restart when the process shows up with a PID different from the last one seen.
+    fn update(&mut self, pid: Option<u32>) {
+        let Some(pid) = pid else {
+            return;
+        };
+        if self.pid.is_some_and(|previous| previous != pid) {
+            self.restarts += 1;
+        }
+        self.pid = Some(pid);
+    }
+}
+
+#[derive(Debug)]
+struct Watched {
+    watch: Watch,
+    state: State,
+}
+
+#[derive(Debug, Default)]
+struct Usage {
+    /// Lowest PID of the matching processes, the children are started after their parent.
+    pid: Option<u32>,
+    cpu: f64,
+    memory: f64,
+}
+
+#[derive(Debug)]
+pub(crate) struct Sensor {
+    inner: sysinfo::System,
+    watched: Vec<Watched>,
+    interval: Interval,
+}
+
+impl Sensor {
+    fn refresh(&mut self) {
+        self.inner.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::new()
+                .with_cpu()
+                .with_memory()
+                .with_cmd(UpdateKind::OnlyIfNotSet),
+        );
+    }
+
+    fn usage(&self, watch: &Watch) -> Usage {
+        self.inner
+            .processes()
+            .iter()
+            // threads are listed as processes on linux
+            .filter(|(_, process)| process.thread_kind().is_none())
+            // a killed process stays as a zombie until its parent reaps it
+            .filter(|(_, process)| {
+                !matches!(
+                    process.status(),
+                    ProcessStatus::Zombie | ProcessStatus::Dead
+                )
+            })
+            .filter(|(_, process)| {
+                let name = process.name().to_string_lossy();
+                let cmdline = process
+                    .cmd()
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                watch.matches(&name, &cmdline)
+            })
+            .fold(Usage::default(), |mut acc, (pid, process)| {
+                let pid = pid.as_u32();
+                acc.pid = Some(acc.pid.map_or(pid, |current| current.min(pid)));
+                acc.cpu += process.cpu_usage() as f64;
+                acc.memory += process.memory() as f64;
+                acc
+            })
+    }
+
+    async fn iterate(&mut self, buffer: &mut Collector) -> anyhow::Result<()> {
+        self.refresh();
+        let now = chezmoi_database::helper::now();
+        let usages = self
+            .watched
+            .iter()
+            .map(|item| self.usage(&item.watch))
+            .collect::<Vec<_>>();
+        for (item, usage) in self.watched.iter_mut().zip(usages) {
+            item.state.update(usage.pid);
+            let name = item.watch.name.clone();
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(PROCESS_RUNNING).with_tag("process", name.clone()),
+                value: MetricValue::bool(usage.pid.is_some()),
+            });
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(PROCESS_RESTARTS).with_tag("process", name.clone()),
+                value: MetricValue::count(item.state.restarts),
+            });
+            if usage.pid.is_some() {
+                buffer.collect(Metric {
+                    timestamp: now,
+                    header: MetricHeader::new(PROCESS_CPU_USAGE).with_tag("process", name.clone()),
+                    value: MetricValue::gauge(usage.cpu),
+                });
+                buffer.collect(Metric {
+                    timestamp: now,
+                    header: MetricHeader::new(PROCESS_MEMORY).with_tag("process", name),
+                    value: MetricValue::gauge(usage.memory),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "process", skip_all)]
+    pub async fn run(mut self, context: super::Context) -> anyhow::Result<()> {
+        let mut collector = super::Collector::new(super::Cache::default(), self.watched.len() * 4);
+        while context.state.is_running() {
+            self.interval.tick().await;
+            if let Err(error) = self.iterate(&mut collector).await {
+                tracing::error!(message = "unable to collect metrics", cause = %error);
+            }
+
+            context.send_all(collector.flush()).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn should_match_by_name_or_cmdline() {
+        let watch = super::Watch {
+            name: "mosquitto".into(),
+            cmdline: None,
+        };
+        assert!(watch.matches("mosquitto", "/usr/sbin/mosquitto -c /etc/mosquitto.conf"));
+        assert!(!watch.matches("mosquitto_sub", "mosquitto_sub -t #"));
+
+        let watch = super::Watch {
+            name: "home-assistant".into(),
+            cmdline: Some("homeassistant --config".into()),
+        };
+        assert!(watch.matches("python3", "python3 -m homeassistant --config /config"));
+        assert!(!watch.matches("python3", "python3 -m http.server"));
+    }
+
+    #[test]
+    fn should_count_restarts() {
+        let mut state = super::State::default();
+        state.update(None);
+        state.update(Some(42));
+        state.update(Some(42));
+        assert_eq!(state.restarts, 0);
+        // stopped then started again
+        state.update(None);
+        state.update(Some(51));
+        assert_eq!(state.restarts, 1);
+        // restarted between two iterations
+        state.update(Some(60));
+        assert_eq!(state.restarts, 2);
+    }
+}
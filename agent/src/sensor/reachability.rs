@@ -0,0 +1,286 @@
+use std::io::ErrorKind;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use chezmoi_database::metrics::entity::{Metric, MetricValue};
+use chezmoi_database::metrics::MetricHeader;
+use tokio::time::Interval;
+
+use super::Collector;
+
+/// Round trip time to the host, in milliseconds, tagged with `host`.
+pub const REACHABILITY_LATENCY: &str = "probe.reachability.latency";
+/// Whether the host answered, tagged with `host`.
+pub const REACHABILITY_REACHABLE: &str = "probe.reachability.reachable";
+
+fn default_interval() -> u64 {
+    30
+}
+
+fn default_timeout() -> u64 {
+    2
+}
+
+/// Device to look for on the network.
+///
+/// With a `port`, a TCP connection is opened, a refused connection meaning the host is up.
+/// Otherwise, the system `ping` command is used, which requires ICMP to be permitted. Those
+/// hosts are skipped when the command is not installed.
+///
+/// ```toml
+/// [[agent.reachability.hosts]]
+/// name = "nas"
+/// address = "192.168.1.10"
+/// port = 22
+///
+/// [[agent.reachability.hosts]]
+/// name = "alice-phone"
+/// address = "192.168.1.42"
+/// ```
+#[derive(Clone, Debug, serde::Deserialize)]
+pub(crate) struct Host {
+    /// Used as the `host` tag.
+    name: String,
+    /// IP address or hostname.
+    address: String,
+    #[serde(default)]
+    port: Option<u16>,
+}
+
+impl Host {
+    fn header(&self, name: &'static str) -> MetricHeader {
+        MetricHeader::new(name).with_tag("host", self.name.clone())
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    #[serde(default = "default_interval")]
+    interval: u64,
+    /// Time after which a host is considered unreachable, in seconds.
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+    #[serde(default)]
+    hosts: Vec<Host>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: default_interval(),
+            timeout: default_timeout(),
+            hosts: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn build(&self) -> Sensor {
+        Sensor {
+            hosts: self.hosts.clone(),
+            timeout: Duration::from_secs(self.timeout),
+            ping: "ping",
+            ping_unavailable: false,
+            interval: tokio::time::interval(Duration::from_secs(self.interval)),
+        }
+    }
+}
+
+/// Reads the round trip time, in milliseconds, from the output of `ping`.
+fn parse_ping_output(output: &str) -> Option<f64> {
+    let (_, rest) = output.split_once("time=")?;
+    let value = rest.split(|c: char| c.is_whitespace() || c == 'm').next()?;
+    value.parse().ok()
+}
+
+/// Whether the command to run could not be found.
+fn is_not_found(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<std::io::Error>()
+        .is_some_and(|error| error.kind() == ErrorKind::NotFound)
+}
+
+#[derive(Debug)]
+pub(crate) struct Sensor {
+    hosts: Vec<Host>,
+    timeout: Duration,
+    ping: &'static str,
+    /// Set once the missing command has been reported, to avoid a warning on every tick.
+    ping_unavailable: bool,
+    interval: Interval,
+}
+
+impl Sensor {
+    /// Returns the latency in milliseconds, `None` when the host didn't answer.
+    async fn check_tcp(&self, address: &str, port: u16) -> Option<f64> {
+        let start = Instant::now();
+        let connect = tokio::net::TcpStream::connect((address, port));
+        match tokio::time::timeout(self.timeout, connect).await {
+            Ok(Ok(_)) => Some(start.elapsed().as_secs_f64() * 1000.0),
+            // the host answered with a reset
+            Ok(Err(error)) if error.kind() == ErrorKind::ConnectionRefused => {
+                Some(start.elapsed().as_secs_f64() * 1000.0)
+            }
+            Ok(Err(_)) | Err(_) => None,
+        }
+    }
+
+    /// Returns the latency in milliseconds, `None` when the host didn't answer.
+    async fn check_icmp(&self, address: &str) -> anyhow::Result<Option<f64>> {
+        let output = tokio::process::Command::new(self.ping)
+            .arg("-n")
+            .args(["-c", "1"])
+            .args(["-W", &self.timeout.as_secs().max(1).to_string()])
+            .arg(address)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(parse_ping_output(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    async fn check(&self, host: &Host) -> anyhow::Result<Option<f64>> {
+        match host.port {
+            Some(port) => Ok(self.check_tcp(&host.address, port).await),
+            None => self.check_icmp(&host.address).await,
+        }
+    }
+
+    async fn iterate(&mut self, buffer: &mut Collector) -> anyhow::Result<()> {
+        let results =
+            futures::future::join_all(self.hosts.iter().map(|host| self.check(host))).await;
+        let now = chezmoi_database::helper::now();
+        let mut ping_missing = None;
+        for (host, result) in self.hosts.iter().zip(results) {
+            let latency = match result {
+                Ok(latency) => {
+                    if host.port.is_none() {
+                        ping_missing = Some(false);
+                    }
+                    latency
+                }
+                // without the command, nothing can be said about the ICMP hosts
+                Err(error) if host.port.is_none() && is_not_found(&error) => {
+                    ping_missing = Some(true);
+                    continue;
+                }
+                Err(error) => {
+                    tracing::error!(message = "unable to check host", host = host.name, cause = %error);
+                    continue;
+                }
+            };
+            buffer.collect(Metric {
+                timestamp: now,
+                header: host.header(REACHABILITY_REACHABLE),
+                value: MetricValue::bool(latency.is_some()),
+            });
+            if let Some(latency) = latency {
+                buffer.collect(Metric {
+                    timestamp: now,
+                    header: host.header(REACHABILITY_LATENCY),
+                    value: MetricValue::gauge(latency),
+                });
+            }
+        }
+        match ping_missing {
+            Some(true) if !self.ping_unavailable => {
+                tracing::warn!(
+                    message = "unable to find the ping command, skipping hosts without port",
+                    command = self.ping
+                );
+                self.ping_unavailable = true;
+            }
+            Some(false) => self.ping_unavailable = false,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "reachability", skip_all)]
+    pub async fn run(mut self, context: super::Context) -> anyhow::Result<()> {
+        let mut collector = super::Collector::new(super::Cache::default(), self.hosts.len() * 2);
+        while context.state.is_running() {
+            self.interval.tick().await;
+            if let Err(error) = self.iterate(&mut collector).await {
+                tracing::error!(message = "unable to collect metrics", cause = %error);
+            }
+
+            context.send_all(collector.flush()).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn should_parse_ping_output() {
+        let output = "PING 192.168.1.1 (192.168.1.1) 56(84) bytes of data.
+64 bytes from 192.168.1.1: icmp_seq=1 ttl=64 time=0.512 ms
+
+--- 192.168.1.1 ping statistics ---
+1 packets transmitted, 1 received, 0% packet loss, time 0ms
+rtt min/avg/max/mdev = 0.512/0.512/0.512/0.000 ms
+";
+        assert_eq!(super::parse_ping_output(output), Some(0.512));
+        // busybox
+        let output = "64 bytes from 10.0.0.2: seq=0 ttl=64 time=12.3 ms";
+        assert_eq!(super::parse_ping_output(output), Some(12.3));
+        assert_eq!(super::parse_ping_output("1 packets transmitted"), None);
+    }
+
+    #[tokio::test]
+    async fn should_check_tcp_hosts() {
+        let sensor = super::Config::default().build();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open = listener.local_addr().unwrap().port();
+        assert!(sensor.check_tcp("127.0.0.1", open).await.is_some());
+
+        // a refused connection still means the host is up
+        let closed = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        assert!(sensor.check_tcp("127.0.0.1", closed).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn should_skip_icmp_hosts_without_ping() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut sensor = super::Config {
+            hosts: vec![
+                super::Host {
+                    name: "nas".into(),
+                    address: "127.0.0.1".into(),
+                    port: Some(port),
+                },
+                super::Host {
+                    name: "phone".into(),
+                    address: "127.0.0.1".into(),
+                    port: None,
+                },
+            ],
+            ..Default::default()
+        }
+        .build();
+        sensor.ping = "chezmoi-missing-ping";
+
+        let mut collector = crate::sensor::Collector::new(Default::default(), 4);
+        sensor.iterate(&mut collector).await.unwrap();
+        assert!(sensor.ping_unavailable);
+        let metrics = collector.flush().unwrap();
+        assert!(!metrics.is_empty());
+        assert!(metrics.iter().all(|metric| metric
+            .header
+            .tags
+            .get("host")
+            .and_then(|value| value.as_text())
+            == Some("nas")));
+    }
+}
@@ -2,12 +2,22 @@ use std::time::Duration;
 
 use chezmoi_database::metrics::entity::{Metric, MetricValue};
 use chezmoi_database::metrics::MetricHeader;
-use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind};
+use sysinfo::{
+    CpuRefreshKind, MemoryRefreshKind, ProcessRefreshKind, ProcessesToUpdate, RefreshKind,
+};
 use tokio::time::Interval;
 
 use super::Collector;
 
 pub const GLOBAL_CPU_USAGE: &str = "host.system.global_cpu.usage";
+/// Usage of a single core, tagged with its index as `core`.
+pub const CPU_USAGE: &str = "host.system.cpu.usage";
+pub const LOAD_AVERAGE_1M: &str = "host.system.load_average.1m";
+pub const LOAD_AVERAGE_5M: &str = "host.system.load_average.5m";
+pub const LOAD_AVERAGE_15M: &str = "host.system.load_average.15m";
+/// Time since boot, in seconds.
+pub const UPTIME: &str = "host.system.uptime";
+pub const PROCESS_COUNT: &str = "host.system.process.count";
 pub const MEMORY_TOTAL: &str = "host.system.memory.total";
 pub const MEMORY_USED: &str = "host.system.memory.used";
 pub const MEMORY_RATIO: &str = "host.system.memory.ratio";
@@ -19,10 +29,37 @@ fn default_interval() -> u64 {
     10
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
+const fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, serde::Deserialize)]
 pub(crate) struct Config {
     #[serde(default = "default_interval")]
     interval: u64,
+    /// Reports the usage of each core.
+    #[serde(default = "default_true")]
+    cpu_cores: bool,
+    /// Reports the 1, 5 and 15 minutes load averages.
+    #[serde(default = "default_true")]
+    load_average: bool,
+    #[serde(default = "default_true")]
+    uptime: bool,
+    /// Reports the number of running processes, which requires to list them.
+    #[serde(default = "default_true")]
+    process_count: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: default_interval(),
+            cpu_cores: true,
+            load_average: true,
+            uptime: true,
+            process_count: true,
+        }
+    }
 }
 
 impl Config {
@@ -33,6 +70,10 @@ impl Config {
                     .with_cpu(CpuRefreshKind::everything())
                     .with_memory(MemoryRefreshKind::everything()),
             ),
+            cpu_cores: self.cpu_cores,
+            load_average: self.load_average,
+            uptime: self.uptime,
+            process_count: self.process_count,
             interval: tokio::time::interval(Duration::from_secs(self.interval)),
         }
     }
@@ -41,18 +82,87 @@ impl Config {
 #[derive(Debug)]
 pub(crate) struct Sensor {
     inner: sysinfo::System,
+    cpu_cores: bool,
+    load_average: bool,
+    uptime: bool,
+    process_count: bool,
     interval: Interval,
 }
 
 impl Sensor {
+    fn refresh(&mut self) {
+        self.inner.refresh_cpu_usage();
+        self.inner.refresh_memory();
+        if self.process_count {
+            // only the list is needed, dead processes have to be removed to keep the count right
+            self.inner.refresh_processes_specifics(
+                ProcessesToUpdate::All,
+                true,
+                ProcessRefreshKind::new(),
+            );
+        }
+    }
+
+    fn collect_cpu_cores(&self, now: u64, buffer: &mut Collector) {
+        for (index, cpu) in self.inner.cpus().iter().enumerate() {
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(CPU_USAGE).with_tag("core", index as i64),
+                value: MetricValue::gauge(cpu.cpu_usage() as f64),
+            });
+        }
+    }
+
+    fn collect_load_average(&self, now: u64, buffer: &mut Collector) {
+        let load = sysinfo::System::load_average();
+        for (name, value) in [
+            (LOAD_AVERAGE_1M, load.one),
+            (LOAD_AVERAGE_5M, load.five),
+            (LOAD_AVERAGE_15M, load.fifteen),
+        ] {
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(name),
+                value: MetricValue::gauge(value),
+            });
+        }
+    }
+
     async fn iterate(&mut self, buffer: &mut Collector) -> anyhow::Result<()> {
-        self.inner.refresh_all();
+        self.refresh();
         let now = chezmoi_database::helper::now();
         buffer.collect(Metric {
             timestamp: now,
             header: MetricHeader::new(GLOBAL_CPU_USAGE),
             value: MetricValue::gauge(self.inner.global_cpu_usage() as f64),
         });
+        if self.cpu_cores {
+            self.collect_cpu_cores(now, buffer);
+        }
+        if self.load_average {
+            self.collect_load_average(now, buffer);
+        }
+        if self.uptime {
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(UPTIME),
+                value: MetricValue::gauge(sysinfo::System::uptime() as f64),
+            });
+        }
+        if self.process_count {
+            // threads are listed as processes on linux
+            let count = self
+                .inner
+                .processes()
+                .values()
+                .filter(|process| process.thread_kind().is_none())
+                .count();
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(PROCESS_COUNT),
+                value: MetricValue::gauge(count as f64),
+            });
+        }
         let total_memory = self.inner.total_memory() as f64;
         let used_memory = self.inner.used_memory() as f64;
         buffer.collect(Metric {
@@ -65,11 +175,13 @@ impl Sensor {
             header: MetricHeader::new(MEMORY_USED),
             value: MetricValue::gauge(used_memory),
         });
-        buffer.collect(Metric {
-            timestamp: now,
-            header: MetricHeader::new(MEMORY_RATIO),
-            value: MetricValue::gauge(used_memory * 100.0 / total_memory),
-        });
+        if total_memory > 0.0 {
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(MEMORY_RATIO),
+                value: MetricValue::gauge(used_memory * 100.0 / total_memory),
+            });
+        }
         let total_swap = self.inner.total_swap() as f64;
         let used_swap = self.inner.used_swap() as f64;
         buffer.collect(Metric {
@@ -82,17 +194,20 @@ impl Sensor {
             header: MetricHeader::new(SWAP_USED),
             value: MetricValue::gauge(used_swap),
         });
-        buffer.collect(Metric {
-            timestamp: now,
-            header: MetricHeader::new(SWAP_RATIO),
-            value: MetricValue::gauge(used_swap * 100.0 / total_swap),
-        });
+        // the ratio is not defined on hosts without swap
+        if total_swap > 0.0 {
+            buffer.collect(Metric {
+                timestamp: now,
+                header: MetricHeader::new(SWAP_RATIO),
+                value: MetricValue::gauge(used_swap * 100.0 / total_swap),
+            });
+        }
         Ok(())
     }
 
     #[tracing::instrument(name = "system", skip_all)]
     pub async fn run(mut self, context: super::Context) -> anyhow::Result<()> {
-        let mut collector = super::Collector::new(super::Cache::default(), 5);
+        let mut collector = super::Collector::new(super::Cache::default(), 16);
         while context.state.is_running() {
             self.interval.tick().await;
             if let Err(error) = self.iterate(&mut collector).await {
@@ -0,0 +1,242 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chezmoi_database::metrics::entity::{Metric, MetricValue};
+use chezmoi_database::metrics::MetricHeader;
+use tokio::time::Interval;
+
+use super::Collector;
+
+/// Temperature of a thermal zone, in celsius, tagged with `zone` and `label`.
+pub const THERMAL_ZONE_TEMPERATURE: &str = "host.thermal.zone.temperature";
+/// Temperature of a hardware monitoring sensor, in celsius, tagged with `chip` and `label`.
+pub const HWMON_TEMPERATURE: &str = "host.thermal.hwmon.temperature";
+
+fn default_interval() -> u64 {
+    10
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+fn default_root() -> PathBuf {
+    PathBuf::from("/sys/class")
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    #[serde(default = "default_interval")]
+    interval: u64,
+    /// Reads the `thermal/thermal_zone*` entries.
+    #[serde(default = "default_true")]
+    thermal_zone: bool,
+    /// Reads the `hwmon/hwmon*` entries.
+    #[serde(default = "default_true")]
+    hwmon: bool,
+    /// Location of the sysfs classes, only useful when running in a container.
+    #[serde(default = "default_root")]
+    root: PathBuf,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: default_interval(),
+            thermal_zone: true,
+            hwmon: true,
+            root: default_root(),
+        }
+    }
+}
+
+impl Config {
+    pub fn build(&self) -> Sensor {
+        Sensor {
+            root: self.root.clone(),
+            thermal_zone: self.thermal_zone,
+            hwmon: self.hwmon,
+            interval: tokio::time::interval(Duration::from_secs(self.interval)),
+        }
+    }
+}
+
+async fn read_text(path: &Path) -> Option<String> {
+    tokio::fs::read_to_string(path)
+        .await
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+/// Reads a temperature exposed in millidegree celsius.
+async fn read_temperature(path: &Path) -> Option<f64> {
+    let value = read_text(path).await?;
+    value.parse::<i64>().ok().map(|value| value as f64 / 1000.0)
+}
+
+/// Lists the entries of a directory starting with the given prefix, sorted by name.
+async fn list_entries(path: &Path, prefix: &str) -> std::io::Result<Vec<(String, PathBuf)>> {
+    let mut entries = Vec::new();
+    let mut dir = tokio::fs::read_dir(path).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(prefix) {
+            entries.push((name, entry.path()));
+        }
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+async fn read_thermal_zones(root: &Path) -> std::io::Result<Vec<(MetricHeader, f64)>> {
+    let mut res = Vec::new();
+    for (zone, path) in list_entries(&root.join("thermal"), "thermal_zone").await? {
+        let Some(temperature) = read_temperature(&path.join("temp")).await else {
+            continue;
+        };
+        let label = read_text(&path.join("type"))
+            .await
+            .unwrap_or_else(|| zone.clone());
+        res.push((
+            MetricHeader::new(THERMAL_ZONE_TEMPERATURE)
+                .with_tag("zone", zone)
+                .with_tag("label", label),
+            temperature,
+        ));
+    }
+    Ok(res)
+}
+
+async fn read_hwmon(root: &Path) -> std::io::Result<Vec<(MetricHeader, f64)>> {
+    let mut res = Vec::new();
+    for (device, path) in list_entries(&root.join("hwmon"), "hwmon").await? {
+        let chip = read_text(&path.join("name")).await.unwrap_or(device);
+        for (file, input) in list_entries(&path, "temp").await? {
+            let Some(sensor) = file.strip_suffix("_input") else {
+                continue;
+            };
+            let Some(temperature) = read_temperature(&input).await else {
+                continue;
+            };
+            let label = read_text(&path.join(format!("{sensor}_label")))
+                .await
+                .unwrap_or_else(|| sensor.to_string());
+            res.push((
+                MetricHeader::new(HWMON_TEMPERATURE)
+                    .with_tag("chip", chip.clone())
+                    .with_tag("label", label),
+                temperature,
+            ));
+        }
+    }
+    Ok(res)
+}
+
+#[derive(Debug)]
+pub(crate) struct Sensor {
+    root: PathBuf,
+    thermal_zone: bool,
+    hwmon: bool,
+    interval: Interval,
+}
+
+impl Sensor {
+    async fn iterate(&mut self, buffer: &mut Collector) -> anyhow::Result<()> {
+        let now = chezmoi_database::helper::now();
+        let mut values = Vec::new();
+        if self.thermal_zone {
+            match read_thermal_zones(&self.root).await {
+                Ok(found) => values.extend(found),
+                Err(err) => {
+                    tracing::warn!(message = "unable to read thermal zones", cause = %err)
+                }
+            }
+        }
+        if self.hwmon {
+            match read_hwmon(&self.root).await {
+                Ok(found) => values.extend(found),
+                Err(err) => tracing::warn!(message = "unable to read hwmon sensors", cause = %err),
+            }
+        }
+        for (header, value) in values {
+            buffer.collect(Metric {
+                timestamp: now,
+                header,
+                value: MetricValue::gauge(value),
+            });
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "thermal", skip_all)]
+    pub async fn run(mut self, context: super::Context) -> anyhow::Result<()> {
+        let mut collector = super::Collector::new(super::Cache::default(), 10);
+        while context.state.is_running() {
+            self.interval.tick().await;
+            if let Err(error) = self.iterate(&mut collector).await {
+                tracing::error!(message = "unable to collect metrics", cause = %error);
+            }
+
+            context.send_all(collector.flush()).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chezmoi_database::metrics::MetricHeader;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_read_thermal_zones_and_hwmon() {
+        let root = std::env::temp_dir().join(format!("chezmoi-thermal-{}", std::process::id()));
+        write(&root.join("thermal/thermal_zone0/type"), "cpu-thermal\n");
+        write(&root.join("thermal/thermal_zone0/temp"), "52078\n");
+        // zone without readable temperature
+        write(&root.join("thermal/thermal_zone1/type"), "gpu-thermal\n");
+        write(&root.join("thermal/cooling_device0/type"), "fan\n");
+        write(&root.join("hwmon/hwmon0/name"), "nvme\n");
+        write(&root.join("hwmon/hwmon0/temp1_input"), "38850\n");
+        write(&root.join("hwmon/hwmon0/temp1_label"), "Composite\n");
+        write(&root.join("hwmon/hwmon0/temp2_input"), "41850\n");
+        write(&root.join("hwmon/hwmon0/temp2_max"), "81850\n");
+
+        let zones = super::read_thermal_zones(&root).await.unwrap();
+        let hwmon = super::read_hwmon(&root).await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            zones,
+            vec![(
+                MetricHeader::new(super::THERMAL_ZONE_TEMPERATURE)
+                    .with_tag("zone", "thermal_zone0")
+                    .with_tag("label", "cpu-thermal"),
+                52.078
+            )]
+        );
+        assert_eq!(
+            hwmon,
+            vec![
+                (
+                    MetricHeader::new(super::HWMON_TEMPERATURE)
+                        .with_tag("chip", "nvme")
+                        .with_tag("label", "Composite"),
+                    38.85
+                ),
+                (
+                    MetricHeader::new(super::HWMON_TEMPERATURE)
+                        .with_tag("chip", "nvme")
+                        .with_tag("label", "temp2"),
+                    41.85
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use chezmoi_database::metrics::entity::MetricValue;
+
+/// Kind of metric to build from a parsed value.
+#[derive(Clone, Copy, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ValueType {
+    Count,
+    #[default]
+    Gauge,
+    Bool,
+}
+
+/// Reads a value from its text representation, like a message payload or a command output.
+pub(crate) fn parse_text(value: &str, kind: ValueType) -> Option<MetricValue> {
+    let value = value.trim();
+    match kind {
+        ValueType::Count => value.parse().ok().map(MetricValue::count),
+        ValueType::Gauge => value
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .map(MetricValue::gauge),
+        ValueType::Bool => match value.to_lowercase().as_str() {
+            "true" | "on" | "1" | "yes" => Some(MetricValue::bool(true)),
+            "false" | "off" | "0" | "no" => Some(MetricValue::bool(false)),
+            _ => None,
+        },
+    }
+}
+
+/// Reads a value from a JSON document, accepting numbers, booleans and strings.
+pub(crate) fn parse_json(value: &serde_json::Value, kind: ValueType) -> Option<MetricValue> {
+    match (value, kind) {
+        (serde_json::Value::String(inner), _) => parse_text(inner, kind),
+        (serde_json::Value::Number(inner), ValueType::Count) => {
+            inner.as_u64().map(MetricValue::count)
+        }
+        (serde_json::Value::Number(inner), ValueType::Gauge) => {
+            inner.as_f64().map(MetricValue::gauge)
+        }
+        (serde_json::Value::Number(inner), ValueType::Bool) => {
+            inner.as_f64().map(|v| MetricValue::bool(v != 0.0))
+        }
+        (serde_json::Value::Bool(inner), ValueType::Bool) => Some(MetricValue::bool(*inner)),
+        (serde_json::Value::Bool(inner), ValueType::Count) => {
+            Some(MetricValue::count(u64::from(*inner)))
+        }
+        (serde_json::Value::Bool(inner), ValueType::Gauge) => {
+            Some(MetricValue::gauge(if *inner { 1.0 } else { 0.0 }))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,30 @@
+use chezmoi_database::metrics::entity::{create, Metric};
+use futures::future::BoxFuture;
+
+/// Stores the metrics in the local database.
+#[derive(Debug)]
+pub struct Sink {
+    database: chezmoi_database::Client,
+}
+
+impl Sink {
+    pub fn new(database: chezmoi_database::Client) -> Self {
+        Self { database }
+    }
+}
+
+impl super::Sink for Sink {
+    fn name(&self) -> &'static str {
+        "database"
+    }
+
+    fn write<'a>(&'a mut self, batch: &'a [Metric]) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let count = create::Command::new(batch)
+                .execute(self.database.as_ref())
+                .await?;
+            tracing::debug!(message = "stored events", count = count);
+            Ok(())
+        })
+    }
+}
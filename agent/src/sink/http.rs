@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use chezmoi_database::metrics::entity::create::MAX_BATCH_SIZE;
+use chezmoi_database::metrics::entity::Metric;
+use futures::future::BoxFuture;
+use reqwest::StatusCode;
+
+pub(crate) fn default_buffer_capacity() -> usize {
+    10_000
+}
+
+pub(crate) fn default_batch_size() -> usize {
+    500
+}
+
+pub(crate) fn default_retry_interval() -> u64 {
+    30
+}
+
+pub(crate) fn default_timeout() -> u64 {
+    10
+}
+
+/// Configuration of the chezmoi server receiving the collected metrics.
+#[derive(Debug, serde::Deserialize)]
+pub struct Config {
+    /// Base url of the chezmoi server, like `http://192.168.1.10:3000`
+    url: String,
+    /// Maximum number of metrics kept in memory while the server cannot be reached.
+    /// When full, the oldest metrics are dropped.
+    #[serde(default = "default_buffer_capacity")]
+    buffer_capacity: usize,
+    /// Maximum number of metrics sent in a single request, up to 1000.
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    /// Delay in seconds before trying to send pending metrics again.
+    #[serde(default = "default_retry_interval")]
+    retry_interval: u64,
+    /// Request timeout in seconds.
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+}
+
+impl Config {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            buffer_capacity: default_buffer_capacity(),
+            batch_size: default_batch_size(),
+            retry_interval: default_retry_interval(),
+            timeout: default_timeout(),
+        }
+    }
+
+    pub fn build(&self) -> anyhow::Result<Sink> {
+        if self.batch_size > MAX_BATCH_SIZE {
+            tracing::warn!(
+                message = "batch size above the server limit, using the limit",
+                batch_size = self.batch_size,
+                limit = MAX_BATCH_SIZE
+            );
+        }
+        let inner = reqwest::Client::builder()
+            .timeout(Duration::from_secs(self.timeout))
+            .build()
+            .context("building http client")?;
+        Ok(Sink::new(
+            inner,
+            format!("{}/api/metrics", self.url.trim_end_matches('/')),
+            Encoding::Json,
+            self.buffer_capacity,
+            self.batch_size.min(MAX_BATCH_SIZE),
+            self.retry_interval,
+        ))
+    }
+}
+
+/// Format of the body sent to the remote server.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Encoding {
+    /// JSON array of metrics, as expected by a chezmoi server.
+    Json,
+    /// InfluxDB line protocol.
+    LineProtocol,
+}
+
+/// Sends the collected metrics to a remote chezmoi server.
+///
+/// Metrics are buffered until the server acknowledges them, so that nothing gets lost
+/// when the server is temporarily unreachable.
+#[derive(Debug)]
+pub struct Sink {
+    inner: reqwest::Client,
+    url: String,
+    encoding: Encoding,
+    buffer: VecDeque<Metric>,
+    buffer_capacity: usize,
+    batch_size: usize,
+    retry_interval: Duration,
+    /// When the server could not be reached, pending metrics are not sent before this instant.
+    retry_at: Option<Instant>,
+}
+
+impl Sink {
+    pub(crate) fn new(
+        inner: reqwest::Client,
+        url: String,
+        encoding: Encoding,
+        buffer_capacity: usize,
+        batch_size: usize,
+        retry_interval: u64,
+    ) -> Self {
+        Self {
+            inner,
+            url,
+            encoding,
+            buffer: VecDeque::with_capacity(batch_size),
+            buffer_capacity: buffer_capacity.max(1),
+            batch_size: batch_size.max(1),
+            retry_interval: Duration::from_secs(retry_interval),
+            retry_at: None,
+        }
+    }
+
+    fn push(&mut self, batch: Vec<Metric>) {
+        self.buffer.extend(batch);
+        if self.buffer.len() > self.buffer_capacity {
+            let count = self.buffer.len() - self.buffer_capacity;
+            self.buffer.drain(..count);
+            tracing::warn!(
+                message = "buffer full, dropping oldest metrics",
+                count = count
+            );
+        }
+    }
+
+    async fn send(&self, batch: &[Metric]) -> anyhow::Result<()> {
+        let req = self.inner.post(&self.url);
+        let req = match self.encoding {
+            Encoding::Json => req.json(batch),
+            Encoding::LineProtocol => req
+                .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(super::influxdb::encode(batch)),
+        };
+        let res = req.send().await.context("sending metrics")?;
+        let status = res.status();
+        if status.is_success() {
+            Ok(())
+        } else if status.is_client_error()
+            && status != StatusCode::REQUEST_TIMEOUT
+            && status != StatusCode::TOO_MANY_REQUESTS
+        {
+            // the server will never accept this batch, retrying is useless
+            let body = res.text().await.unwrap_or_default();
+            tracing::error!(message = "metrics rejected by server", status = %status, body = body, count = batch.len());
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("unexpected response status {status}"))
+        }
+    }
+
+    /// Sends the pending metrics, by batch, until the buffer is empty or a request fails.
+    async fn send_pending(&mut self) -> anyhow::Result<()> {
+        while !self.buffer.is_empty() {
+            let size = self.batch_size.min(self.buffer.len());
+            self.buffer.make_contiguous();
+            self.send(&self.buffer.as_slices().0[..size]).await?;
+            tracing::debug!(message = "sent events", count = size);
+            self.buffer.drain(..size);
+        }
+        Ok(())
+    }
+
+    async fn try_send_pending(&mut self) -> anyhow::Result<()> {
+        if self.retry_at.is_some_and(|at| at > Instant::now()) {
+            return Ok(());
+        }
+        match self.send_pending().await {
+            Ok(_) => {
+                self.retry_at = None;
+                Ok(())
+            }
+            Err(error) => {
+                self.retry_at = Some(Instant::now() + self.retry_interval);
+                Err(error)
+            }
+        }
+    }
+}
+
+impl super::Sink for Sink {
+    fn name(&self) -> &'static str {
+        match self.encoding {
+            Encoding::Json => "http",
+            Encoding::LineProtocol => "influxdb",
+        }
+    }
+
+    fn write<'a>(&'a mut self, batch: &'a [Metric]) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            self.push(batch.to_vec());
+            self.try_send_pending().await
+        })
+    }
+
+    fn flush(&mut self) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(self.try_send_pending())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chezmoi_database::metrics::entity::{Metric, MetricValue};
+    use chezmoi_database::metrics::MetricHeader;
+
+    use crate::sink::Sink;
+
+    fn metrics(count: u64) -> Vec<Metric> {
+        (0..count)
+            .map(|index| Metric {
+                timestamp: index,
+                header: MetricHeader::new("foo"),
+                value: MetricValue::count(index),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_drop_oldest_metrics_when_full() {
+        let mut config = super::Config::new("http://localhost:3000");
+        config.buffer_capacity = 5;
+        let mut sink = config.build().unwrap();
+        sink.push(metrics(3));
+        sink.push(metrics(4));
+        assert_eq!(sink.buffer.len(), 5);
+        assert_eq!(sink.buffer.front().unwrap().timestamp, 2);
+    }
+
+    #[test]
+    fn should_limit_batch_size_to_server_limit() {
+        let mut config = super::Config::new("http://localhost:3000");
+        config.batch_size = 5000;
+        let sink = config.build().unwrap();
+        assert_eq!(sink.batch_size, super::MAX_BATCH_SIZE);
+    }
+
+    #[tokio::test]
+    async fn should_keep_metrics_when_unreachable() {
+        // nothing should be listening on the discard port
+        let mut sink = super::Config::new("http://127.0.0.1:9").build().unwrap();
+        assert!(sink.write(&metrics(3)).await.is_err());
+        assert_eq!(sink.buffer.len(), 3);
+        // the retry delay is not expired, nothing should be sent
+        assert!(sink.flush().await.is_ok());
+        assert_eq!(sink.buffer.len(), 3);
+    }
+}
@@ -0,0 +1,225 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use anyhow::Context;
+use chezmoi_database::metrics::entity::{Metric, MetricValue};
+use chezmoi_database::metrics::MetricTagValue;
+
+use super::http::{
+    default_batch_size, default_buffer_capacity, default_retry_interval, default_timeout, Encoding,
+    Sink,
+};
+
+/// Configuration of an InfluxDB endpoint receiving the metrics in line protocol.
+#[derive(Debug, serde::Deserialize)]
+pub struct Config {
+    /// Write endpoint, like `http://localhost:8086/api/v2/write?org=home&bucket=chezmoi`
+    url: String,
+    /// API token, sent in the `Authorization` header.
+    #[serde(default)]
+    token: Option<String>,
+    /// Maximum number of metrics kept in memory while the endpoint cannot be reached.
+    /// When full, the oldest metrics are dropped.
+    #[serde(default = "default_buffer_capacity")]
+    buffer_capacity: usize,
+    /// Maximum number of metrics sent in a single request.
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    /// Delay in seconds before trying to send pending metrics again.
+    #[serde(default = "default_retry_interval")]
+    retry_interval: u64,
+    /// Request timeout in seconds.
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+}
+
+impl Config {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            token: None,
+            buffer_capacity: default_buffer_capacity(),
+            batch_size: default_batch_size(),
+            retry_interval: default_retry_interval(),
+            timeout: default_timeout(),
+        }
+    }
+
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn build(&self) -> anyhow::Result<Sink> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(ref token) = self.token {
+            let mut value = reqwest::header::HeaderValue::try_from(format!("Token {token}"))
+                .context("invalid token")?;
+            value.set_sensitive(true);
+            headers.insert(reqwest::header::AUTHORIZATION, value);
+        }
+        let inner = reqwest::Client::builder()
+            .timeout(Duration::from_secs(self.timeout))
+            .default_headers(headers)
+            .build()
+            .context("building http client")?;
+        Ok(Sink::new(
+            inner,
+            self.url.clone(),
+            Encoding::LineProtocol,
+            self.buffer_capacity,
+            self.batch_size,
+            self.retry_interval,
+        ))
+    }
+}
+
+/// Escapes the characters having a meaning in the line protocol.
+fn escape(output: &mut String, value: &str, chars: &[char]) {
+    for c in value.chars() {
+        if c == '\\' || chars.contains(&c) {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+}
+
+fn tag_value(value: &MetricTagValue) -> String {
+    match value {
+        MetricTagValue::Text(inner) => inner.to_string(),
+        MetricTagValue::ArcText(inner) => inner.to_string(),
+        MetricTagValue::Float(inner) => inner.to_string(),
+        MetricTagValue::Int(inner) => inner.to_string(),
+        MetricTagValue::Boolean(inner) => inner.to_string(),
+    }
+}
+
+/// Writes a metric as a single line, returns `false` when the value cannot be represented.
+fn encode_metric(output: &mut String, metric: &Metric) -> bool {
+    let field = match metric.value {
+        MetricValue::Count { value } => format!("{value}i"),
+        MetricValue::Gauge { value } if value.is_finite() => format!("{value}"),
+        MetricValue::Gauge { .. } => return false,
+        MetricValue::Bool { value } => format!("{value}"),
+    };
+    escape(output, metric.header.name.as_ref(), &[',', ' ']);
+    // sorting the tags is recommended for performance
+    let mut tags = metric
+        .header
+        .tags
+        .entries()
+        .map(|(key, value)| (key.as_ref(), tag_value(value)))
+        .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+        .collect::<Vec<_>>();
+    tags.sort_by_key(|(key, _)| *key);
+    for (key, value) in tags {
+        output.push(',');
+        escape(output, key, &[',', '=', ' ']);
+        output.push('=');
+        escape(output, value.as_str(), &[',', '=', ' ']);
+    }
+    let _ = writeln!(
+        output,
+        " value={field} {}",
+        (metric.timestamp as u128) * 1_000_000_000
+    );
+    true
+}
+
+/// Encodes the metrics in line protocol, with timestamps in nanoseconds.
+pub(crate) fn encode(batch: &[Metric]) -> String {
+    let mut output = String::new();
+    for metric in batch {
+        if !encode_metric(&mut output, metric) {
+            tracing::warn!(
+                message = "unable to encode metric",
+                name = %metric.header.name
+            );
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use chezmoi_database::metrics::entity::{Metric, MetricValue};
+    use chezmoi_database::metrics::MetricHeader;
+
+    use crate::sink::Sink;
+
+    fn metrics() -> Vec<Metric> {
+        vec![
+            Metric {
+                timestamp: 10,
+                header: MetricHeader::new("host.system.cpu")
+                    .with_tag("host", "rpi")
+                    .with_tag("core", "cpu 0"),
+                value: MetricValue::gauge(12.5),
+            },
+            Metric {
+                timestamp: 11,
+                header: MetricHeader::new("network,rx"),
+                value: MetricValue::count(42),
+            },
+            Metric {
+                timestamp: 12,
+                header: MetricHeader::new("door").with_tag("room", "a=b"),
+                value: MetricValue::bool(true),
+            },
+            Metric {
+                timestamp: 13,
+                header: MetricHeader::new("broken"),
+                value: MetricValue::gauge(f64::NAN),
+            },
+        ]
+    }
+
+    #[test]
+    fn should_encode_line_protocol() {
+        assert_eq!(
+            super::encode(&metrics()),
+            "host.system.cpu,core=cpu\\ 0,host=rpi value=12.5 10000000000\n\
+            network\\,rx value=42i 11000000000\n\
+            door,room=a\\=b value=true 12000000000\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_push_to_endpoint() {
+        let received = Arc::new(Mutex::new(Vec::<(Option<String>, String)>::new()));
+        let router = axum::Router::new().route(
+            "/api/v2/write",
+            post({
+                let received = received.clone();
+                move |headers: HeaderMap, body: String| async move {
+                    let token = headers
+                        .get("authorization")
+                        .and_then(|value| value.to_str().ok())
+                        .map(String::from);
+                    received.lock().unwrap().push((token, body));
+                    axum::http::StatusCode::NO_CONTENT
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let mut sink = super::Config::new(format!(
+            "http://{address}/api/v2/write?org=home&bucket=chezmoi"
+        ))
+        .with_token("secret")
+        .build()
+        .unwrap();
+        sink.write(&metrics()[..2]).await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].0.as_deref(), Some("Token secret"));
+        assert_eq!(received[0].1.lines().count(), 2);
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use chezmoi_database::metrics::entity::Metric;
+use futures::future::BoxFuture;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct FileConfig {
+    path: PathBuf,
+}
+
+impl FileConfig {
+    pub async fn build(&self) -> anyhow::Result<Sink> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .with_context(|| format!("opening {:?}", self.path))?;
+        Ok(Sink {
+            name: "file",
+            writer: Box::new(file),
+        })
+    }
+}
+
+/// Writes every metric as a JSON object on its own line.
+pub struct Sink {
+    name: &'static str,
+    writer: Box<dyn AsyncWrite + Send + Unpin>,
+}
+
+impl std::fmt::Debug for Sink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sink").field("name", &self.name).finish()
+    }
+}
+
+impl Sink {
+    pub fn stdout() -> Self {
+        Self {
+            name: "stdout",
+            writer: Box::new(tokio::io::stdout()),
+        }
+    }
+}
+
+impl super::Sink for Sink {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn write<'a>(&'a mut self, batch: &'a [Metric]) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut buffer = Vec::new();
+            for metric in batch {
+                serde_json::to_writer(&mut buffer, metric)?;
+                buffer.push(b'\n');
+            }
+            self.writer.write_all(&buffer).await?;
+            self.writer.flush().await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chezmoi_database::metrics::entity::{Metric, MetricValue};
+    use chezmoi_database::metrics::MetricHeader;
+
+    use crate::sink::Sink;
+
+    #[tokio::test]
+    async fn should_write_one_metric_per_line() {
+        let path = std::env::temp_dir().join(format!("chezmoi-{}.jsonl", std::process::id()));
+        let mut sink = super::FileConfig { path: path.clone() }
+            .build()
+            .await
+            .unwrap();
+        let batch = (0..3)
+            .map(|index| Metric {
+                timestamp: index,
+                header: MetricHeader::new("foo").with_tag("host", "rpi"),
+                value: MetricValue::count(index),
+            })
+            .collect::<Vec<_>>();
+        sink.write(&batch).await.unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            r#"{"timestamp":0,"name":"foo","tags":{"host":"rpi"},"value":{"type":"count","value":0}}"#
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chezmoi_database::metrics::entity::Metric;
+use futures::future::BoxFuture;
+use tokio::sync::mpsc;
+
+pub mod database;
+#[cfg(feature = "remote")]
+pub mod http;
+#[cfg(feature = "remote")]
+pub mod influxdb;
+pub mod json_lines;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+
+/// Interval at which the sinks get flushed, to retry sending buffered metrics.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Destination of the metrics collected by the sensors.
+pub trait Sink: Send {
+    fn name(&self) -> &'static str;
+
+    /// Handles a batch of metrics collected by the sensors.
+    fn write<'a>(&'a mut self, batch: &'a [Metric]) -> BoxFuture<'a, anyhow::Result<()>>;
+
+    /// Called periodically and before shutting down, for sinks buffering metrics.
+    fn flush(&mut self) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Sinks that can be configured in the `[agent]` section.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub(crate) enum Config {
+    Stdout,
+    File(json_lines::FileConfig),
+    #[cfg(feature = "remote")]
+    Http(http::Config),
+    #[cfg(feature = "remote")]
+    Influxdb(influxdb::Config),
+    #[cfg(feature = "mqtt")]
+    Mqtt(mqtt::Config),
+}
+
+impl Config {
+    pub async fn build(&self) -> anyhow::Result<Box<dyn Sink>> {
+        Ok(match self {
+            Self::Stdout => Box::new(json_lines::Sink::stdout()),
+            Self::File(inner) => Box::new(inner.build().await?),
+            #[cfg(feature = "remote")]
+            Self::Http(inner) => Box::new(inner.build()?),
+            #[cfg(feature = "remote")]
+            Self::Influxdb(inner) => Box::new(inner.build()?),
+            #[cfg(feature = "mqtt")]
+            Self::Mqtt(inner) => Box::new(inner.build()),
+        })
+    }
+}
+
+#[tracing::instrument(name = "sink", skip_all, fields(sink = sink.name()))]
+pub(crate) async fn run(mut sink: Box<dyn Sink>, mut receiver: mpsc::Receiver<Arc<[Metric]>>) {
+    let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            batch = receiver.recv() => match batch {
+                Some(batch) => {
+                    if let Err(error) = sink.write(&batch).await {
+                        tracing::error!(message = "unable to write metrics", cause = %error);
+                    }
+                }
+                None => break,
+            },
+            _ = interval.tick() => {
+                if let Err(error) = sink.flush().await {
+                    tracing::warn!(message = "unable to flush metrics", cause = %error);
+                }
+            }
+        }
+    }
+    if let Err(error) = sink.flush().await {
+        tracing::error!(message = "unable to flush remaining metrics", cause = %error);
+    }
+}
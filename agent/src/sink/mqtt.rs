@@ -0,0 +1,366 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chezmoi_database::metrics::entity::{Metric, MetricValue};
+use chezmoi_database::metrics::MetricTagValue;
+use futures::future::BoxFuture;
+use rumqttc::{AsyncClient, QoS};
+use tokio::task::JoinHandle;
+
+use crate::mqtt::BrokerConfig;
+
+const DEFAULT_CLIENT_ID: &str = "chezmoi-agent-sink";
+
+/// Delay before polling the broker connection again after a failure.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+fn default_topic_prefix() -> String {
+    String::from("chezmoi")
+}
+
+fn default_discovery_prefix() -> String {
+    String::from("homeassistant")
+}
+
+fn default_capacity() -> usize {
+    1000
+}
+
+/// Home Assistant sensor exposing a metric of a bluetooth device.
+struct Component {
+    metric: &'static str,
+    model: &'static str,
+    key: &'static str,
+    name: &'static str,
+    device_class: Option<&'static str>,
+    unit: &'static str,
+}
+
+const COMPONENTS: &[Component] = &[
+    Component {
+        metric: "atc-thermometer.temperature",
+        model: "ATC thermometer",
+        key: "temperature",
+        name: "Temperature",
+        device_class: Some("temperature"),
+        unit: "°C",
+    },
+    Component {
+        metric: "atc-thermometer.humidity",
+        model: "ATC thermometer",
+        key: "humidity",
+        name: "Humidity",
+        device_class: Some("humidity"),
+        unit: "%",
+    },
+    Component {
+        metric: "atc-thermometer.battery",
+        model: "ATC thermometer",
+        key: "battery",
+        name: "Battery",
+        device_class: Some("battery"),
+        unit: "%",
+    },
+    Component {
+        metric: "miflora.temperature",
+        model: "Miflora",
+        key: "temperature",
+        name: "Temperature",
+        device_class: Some("temperature"),
+        unit: "°C",
+    },
+    Component {
+        metric: "miflora.brightness",
+        model: "Miflora",
+        key: "brightness",
+        name: "Brightness",
+        device_class: Some("illuminance"),
+        unit: "lx",
+    },
+    Component {
+        metric: "miflora.moisture",
+        model: "Miflora",
+        key: "moisture",
+        name: "Moisture",
+        device_class: Some("moisture"),
+        unit: "%",
+    },
+    Component {
+        metric: "miflora.conductivity",
+        model: "Miflora",
+        key: "conductivity",
+        name: "Conductivity",
+        device_class: None,
+        unit: "µS/cm",
+    },
+    Component {
+        metric: "miflora.battery",
+        model: "Miflora",
+        key: "battery",
+        name: "Battery",
+        device_class: Some("battery"),
+        unit: "%",
+    },
+];
+
+/// Publishes the metrics to an MQTT broker, one topic per serie.
+///
+/// ```toml
+/// [[agent.sinks]]
+/// type = "mqtt"
+/// host = "192.168.1.10"
+/// discovery = true
+/// ```
+#[derive(Debug, serde::Deserialize)]
+pub struct Config {
+    #[serde(flatten)]
+    broker: BrokerConfig,
+    /// Prefix of the topics, a metric `miflora.moisture` with the address `C4:7C:8D:6A:3E:1F`
+    /// is published on `chezmoi/miflora/moisture/C4:7C:8D:6A:3E:1F`.
+    #[serde(default = "default_topic_prefix")]
+    topic_prefix: String,
+    /// Publish the Home Assistant discovery configuration of the known devices.
+    #[serde(default)]
+    discovery: bool,
+    #[serde(default = "default_discovery_prefix")]
+    discovery_prefix: String,
+    /// Keep the last value of each topic on the broker.
+    #[serde(default)]
+    retain: bool,
+    /// Maximum number of messages waiting to be sent to the broker.
+    #[serde(default = "default_capacity")]
+    capacity: usize,
+}
+
+impl Config {
+    pub fn new(broker: BrokerConfig) -> Self {
+        Self {
+            broker,
+            topic_prefix: default_topic_prefix(),
+            discovery: false,
+            discovery_prefix: default_discovery_prefix(),
+            retain: false,
+            capacity: default_capacity(),
+        }
+    }
+
+    pub fn with_discovery(mut self, discovery: bool) -> Self {
+        self.discovery = discovery;
+        self
+    }
+
+    pub fn build(&self) -> Sink {
+        let (client, mut eventloop) =
+            AsyncClient::new(self.broker.options(DEFAULT_CLIENT_ID), self.capacity.max(1));
+        let task = tokio::spawn(async move {
+            loop {
+                if let Err(error) = eventloop.poll().await {
+                    tracing::warn!(message = "mqtt connection failed", cause = %error);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        });
+        Sink {
+            client,
+            task,
+            topic_prefix: self.topic_prefix.trim_end_matches('/').to_string(),
+            discovery: self
+                .discovery
+                .then(|| self.discovery_prefix.trim_end_matches('/').to_string()),
+            discovered: HashSet::default(),
+            retain: self.retain,
+        }
+    }
+}
+
+fn address(metric: &Metric) -> Option<String> {
+    match metric.header.tags.0.get(crate::ADDRESS)? {
+        MetricTagValue::Text(inner) => Some(inner.to_string()),
+        MetricTagValue::ArcText(inner) => Some(inner.to_string()),
+        _ => None,
+    }
+}
+
+fn payload(value: &MetricValue) -> String {
+    match value {
+        MetricValue::Count { value } => value.to_string(),
+        MetricValue::Gauge { value } => value.to_string(),
+        MetricValue::Bool { value } => value.to_string(),
+    }
+}
+
+pub struct Sink {
+    client: AsyncClient,
+    task: JoinHandle<()>,
+    topic_prefix: String,
+    /// Prefix of the discovery topics, when enabled.
+    discovery: Option<String>,
+    /// Devices and metrics for which the discovery configuration got published.
+    discovered: HashSet<(String, &'static str)>,
+    retain: bool,
+}
+
+impl Drop for Sink {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Sink {
+    fn topic(&self, metric: &Metric, address: Option<&str>) -> String {
+        let mut topic = format!(
+            "{}/{}",
+            self.topic_prefix,
+            metric.header.name.replace('.', "/")
+        );
+        if let Some(address) = address {
+            topic.push('/');
+            topic.push_str(address);
+        }
+        topic
+    }
+
+    fn discovery_message(
+        &self,
+        prefix: &str,
+        component: &Component,
+        address: &str,
+        state_topic: &str,
+    ) -> (String, String) {
+        let device_id = format!("chezmoi_{}", address.replace(':', "").to_lowercase());
+        let topic = format!("{prefix}/sensor/{device_id}/{}/config", component.key);
+        let mut payload = serde_json::json!({
+            "name": component.name,
+            "unique_id": format!("{device_id}_{}", component.key),
+            "state_topic": state_topic,
+            "state_class": "measurement",
+            "unit_of_measurement": component.unit,
+            "device": {
+                "identifiers": [device_id],
+                "connections": [["bluetooth", address]],
+                "name": format!("{} {address}", component.model),
+                "model": component.model,
+            },
+        });
+        if let Some(device_class) = component.device_class {
+            payload["device_class"] = serde_json::Value::from(device_class);
+        }
+        (topic, payload.to_string())
+    }
+
+    /// Builds the messages to publish for a metric, starting with the discovery
+    /// configuration the first time a known device metric is seen.
+    fn messages(&mut self, metric: &Metric) -> Vec<(String, String, bool)> {
+        let address = address(metric);
+        let topic = self.topic(metric, address.as_deref());
+        let mut res = Vec::with_capacity(2);
+        if let (Some(prefix), Some(address)) = (self.discovery.as_deref(), address) {
+            if let Some(component) = COMPONENTS
+                .iter()
+                .find(|item| item.metric == metric.header.name)
+            {
+                if self.discovered.insert((address.clone(), component.metric)) {
+                    let (topic, payload) =
+                        self.discovery_message(prefix, component, &address, &topic);
+                    res.push((topic, payload, true));
+                }
+            }
+        }
+        res.push((topic, payload(&metric.value), self.retain));
+        res
+    }
+}
+
+impl super::Sink for Sink {
+    fn name(&self) -> &'static str {
+        "mqtt"
+    }
+
+    fn write<'a>(&'a mut self, batch: &'a [Metric]) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut dropped = 0;
+            for metric in batch {
+                for (topic, payload, retain) in self.messages(metric) {
+                    // never block the sink when the broker cannot be reached
+                    if self
+                        .client
+                        .try_publish(topic, QoS::AtLeastOnce, retain, payload)
+                        .is_err()
+                    {
+                        dropped += 1;
+                    }
+                }
+            }
+            if dropped > 0 {
+                anyhow::bail!("{dropped} messages dropped, unable to reach the broker");
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chezmoi_database::metrics::entity::{Metric, MetricValue};
+    use chezmoi_database::metrics::MetricHeader;
+
+    use crate::mqtt::broker::Broker;
+    use crate::mqtt::BrokerConfig;
+    use crate::sink::Sink;
+
+    #[tokio::test]
+    async fn should_publish_metrics_and_discovery() {
+        let broker = Broker::start().await;
+        let mut sink = super::Config::new(BrokerConfig::new("127.0.0.1", broker.address.port()))
+            .with_discovery(true)
+            .build();
+
+        let metrics = vec![
+            Metric {
+                timestamp: 0,
+                header: MetricHeader::new("miflora.moisture")
+                    .with_tag(crate::ADDRESS, "C4:7C:8D:6A:3E:1F"),
+                value: MetricValue::gauge(21.0),
+            },
+            Metric {
+                timestamp: 0,
+                header: MetricHeader::new("host.system.memory.ratio"),
+                value: MetricValue::gauge(0.5),
+            },
+        ];
+        sink.write(&metrics).await.unwrap();
+        // discovery is only published once per device metric
+        sink.write(&metrics[..1]).await.unwrap();
+
+        for _ in 0..50 {
+            if broker.received.lock().unwrap().len() >= 4 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let received = broker.received.lock().unwrap();
+        let topics = received
+            .iter()
+            .map(|item| item.topic.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            topics,
+            vec![
+                "homeassistant/sensor/chezmoi_c47c8d6a3e1f/moisture/config",
+                "chezmoi/miflora/moisture/C4:7C:8D:6A:3E:1F",
+                "chezmoi/host/system/memory/ratio",
+                "chezmoi/miflora/moisture/C4:7C:8D:6A:3E:1F",
+            ]
+        );
+        assert!(received[0].retain);
+        let config: serde_json::Value = serde_json::from_slice(&received[0].payload).unwrap();
+        assert_eq!(
+            config["state_topic"],
+            "chezmoi/miflora/moisture/C4:7C:8D:6A:3E:1F"
+        );
+        assert_eq!(config["device_class"], "moisture");
+        assert_eq!(received[1].payload.as_ref(), b"21");
+    }
+}
@@ -1,16 +1,21 @@
+use std::borrow::Cow;
 use std::sync::LazyLock;
 
 use another_html_builder::{Body, Buffer};
 
+use crate::component::helper::format_datetime;
+
 static POWER_FORMATTER: LazyLock<human_number::Formatter<'static>> =
     LazyLock::new(human_number::Formatter::si);
 
 #[derive(Debug)]
 pub struct DeviceValues<'a> {
-    pub address: &'a str,
-    pub name: Option<&'a str>,
+    pub address: Cow<'a, str>,
+    pub name: Option<Cow<'a, str>>,
     pub tx_power: f64,
+    /// Battery level, in percent.
     pub battery: Option<f64>,
+    /// Moment the device was last seen.
     pub timestamp: u64,
 }
 
@@ -32,10 +37,13 @@ impl<'a> Card<'a> {
     ) -> Buffer<W, Body<'v>> {
         buf.node("div")
             .attr(("class", "flex-row m-sm mx-md"))
+            .attr(("data-address", device.address.as_ref()))
             .content(|buf| {
                 buf.node("div")
                     .attr(("class", "flex-1"))
-                    .content(|buf| buf.text(device.name.as_deref().unwrap_or(device.address)))
+                    .content(|buf| {
+                        buf.text(device.name.as_deref().unwrap_or(device.address.as_ref()))
+                    })
                     .node("div")
                     .content(|buf| buf.raw(POWER_FORMATTER.format(device.tx_power)))
                     .node("progress")
@@ -43,6 +51,18 @@ impl<'a> Card<'a> {
                     .attr(("max", 100))
                     .attr(("min", 0))
                     .content(|buf| buf)
+                    .node("div")
+                    .attr(("class", "mx-sm"))
+                    .content(|buf| match device.battery {
+                        Some(value) => buf.text(&format!("{value:.0} %")),
+                        None => buf.text("-"),
+                    })
+                    .node("div")
+                    .attr(("class", "mx-sm"))
+                    .content(|buf| match format_datetime(device.timestamp) {
+                        Some(dt) => buf.raw(dt),
+                        None => buf.text("-"),
+                    })
             })
     }
 
@@ -0,0 +1,100 @@
+use std::borrow::Cow;
+
+use another_html_builder::{Body, Buffer};
+
+#[derive(Debug)]
+pub struct DeviceValues<'a> {
+    pub name: Cow<'a, str>,
+    /// Unknown when the device wasn't checked yet.
+    pub reachable: Option<bool>,
+    /// Latest round trip time, in milliseconds.
+    pub latency: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Card<'a> {
+    devices: Vec<DeviceValues<'a>>,
+}
+
+impl<'a> Card<'a> {
+    pub fn new(mut devices: Vec<DeviceValues<'a>>) -> Self {
+        // online devices first
+        devices.sort_by(|first, second| {
+            second
+                .reachable
+                .unwrap_or(false)
+                .cmp(&first.reachable.unwrap_or(false))
+                .then_with(|| first.name.cmp(&second.name))
+        });
+        Self { devices }
+    }
+
+    fn online_count(&self) -> usize {
+        self.devices
+            .iter()
+            .filter(|device| device.reachable == Some(true))
+            .count()
+    }
+
+    fn render_device_row<'v, W: std::fmt::Write>(
+        &self,
+        buf: Buffer<W, Body<'v>>,
+        device: &DeviceValues<'a>,
+    ) -> Buffer<W, Body<'v>> {
+        let (status, classname) = match device.reachable {
+            Some(true) => ("online", "flex-row m-sm mx-md"),
+            Some(false) => ("offline", "flex-row m-sm mx-md text-muted"),
+            None => ("-", "flex-row m-sm mx-md text-muted"),
+        };
+        buf.node("div")
+            .attr(("class", classname))
+            .attr(("data-device", device.name.as_ref()))
+            .content(|buf| {
+                buf.node("div")
+                    .attr(("class", "flex-1"))
+                    .content(|buf| buf.text(device.name.as_ref()))
+                    .node("div")
+                    .attr(("class", "mx-sm"))
+                    .content(|buf| match (device.reachable, device.latency) {
+                        (Some(true), Some(value)) => buf.text(&format!("{value:.1} ms")),
+                        _ => buf.text("-"),
+                    })
+                    .node("div")
+                    .attr(("class", "mx-sm"))
+                    .content(|buf| buf.text(status))
+            })
+    }
+}
+
+impl<'a> crate::component::prelude::Component for Card<'a> {
+    fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        buf.node("div")
+            .attr((
+                "class",
+                "card devices-online shadow min-w-500px h-150px m-md flex-col",
+            ))
+            .content(|buf| {
+                buf.node("div")
+                    .attr(("class", "card-content flex-1 scroll-y py-md"))
+                    .content(|buf| {
+                        if self.devices.is_empty() {
+                            buf.node("p")
+                                .attr(("class", "text-center"))
+                                .content(|buf| buf.text("No device checked"))
+                        } else {
+                            self.devices
+                                .iter()
+                                .fold(buf, |buf, item| self.render_device_row(buf, item))
+                        }
+                    })
+                    .node("div")
+                    .attr(("class", "card-footer"))
+                    .content(|buf| {
+                        buf.text("Devices online ")
+                            .raw(self.online_count())
+                            .raw("/")
+                            .raw(self.devices.len())
+                    })
+            })
+    }
+}
@@ -0,0 +1,16 @@
+use another_html_builder::{Body, Buffer};
+
+#[derive(Debug)]
+pub struct Card<'a>(super::binary_usage::Card<'a>);
+
+impl<'a> Card<'a> {
+    pub fn new(mount: &'a str, total: Option<f64>, used: Option<f64>) -> Self {
+        Self(super::binary_usage::Card::new(mount, total, used))
+    }
+}
+
+impl<'a> crate::component::prelude::Component for Card<'a> {
+    fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        self.0.render(buf)
+    }
+}
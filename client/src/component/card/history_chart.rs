@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::ops::Range;
 
 use another_html_builder::{Body, Buffer};
@@ -8,14 +9,14 @@ use crate::size::{Dimension, Size};
 
 #[derive(Debug)]
 pub struct Card<'a> {
-    title: &'a str,
+    title: Cow<'a, str>,
     dimension: Dimension,
     content: LineChart<'a>,
 }
 
 impl<'a> Card<'a> {
     pub fn new(
-        title: &'a str,
+        title: impl Into<Cow<'a, str>>,
         dimension: Dimension,
         series: Vec<Serie<'a>>,
         x_range: Option<Range<u64>>,
@@ -39,7 +40,7 @@ impl<'a> Card<'a> {
         );
 
         Self {
-            title,
+            title: title.into(),
             dimension,
             content,
         }
@@ -62,7 +63,7 @@ impl<'a> crate::component::prelude::Component for Card<'a> {
                     .content(|buf| self.content.render(buf))
                     .node("div")
                     .attr(("class", "card-footer"))
-                    .content(|buf| buf.text(self.title))
+                    .content(|buf| buf.text(self.title.as_ref()))
             })
     }
 }
@@ -164,10 +164,10 @@ impl Values {
         self.temperature
             .map(|v| v.timestamp)
             .into_iter()
-            .chain(self.brightness.map(|v| v.timestamp).into_iter())
-            .chain(self.moisture.map(|v| v.timestamp).into_iter())
-            .chain(self.conductivity.map(|v| v.timestamp).into_iter())
-            .chain(self.battery.map(|v| v.timestamp).into_iter())
+            .chain(self.brightness.map(|v| v.timestamp))
+            .chain(self.moisture.map(|v| v.timestamp))
+            .chain(self.conductivity.map(|v| v.timestamp))
+            .chain(self.battery.map(|v| v.timestamp))
             .max()
     }
 }
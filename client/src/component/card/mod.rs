@@ -2,21 +2,38 @@ pub mod atc_thermometer;
 mod binary_usage;
 pub mod bluetooth_devices;
 pub(crate) mod container;
+pub mod devices_online;
+pub mod disk;
 pub mod history_chart;
 pub mod miflora;
+pub mod network;
+pub mod process;
 pub mod system_cpu;
+pub mod system_cpu_cores;
+pub mod system_load;
 pub mod system_memory;
 pub mod system_swap;
+pub mod thermal;
+pub mod uptime;
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum AnyCard<'a> {
     AtcThermometer(atc_thermometer::Card<'a>),
     BluetoothDevices(bluetooth_devices::Card<'a>),
     Cpu(system_cpu::Card),
+    CpuCores(system_cpu_cores::Card),
+    DevicesOnline(devices_online::Card<'a>),
+    Disk(disk::Card<'a>),
     HistoryChart(history_chart::Card<'a>),
+    Load(system_load::Card),
     Memory(system_memory::Card),
     Miflora(miflora::Card<'a>),
+    Network(network::Card<'a>),
+    Process(process::Card<'a>),
     Swap(system_swap::Card),
+    Thermal(thermal::Card<'a>),
+    Uptime(uptime::Card<'a>),
 }
 
 impl<'a> super::prelude::Component for AnyCard<'a> {
@@ -28,10 +45,18 @@ impl<'a> super::prelude::Component for AnyCard<'a> {
             Self::AtcThermometer(inner) => inner.render(buf),
             Self::BluetoothDevices(inner) => inner.render(buf),
             Self::Cpu(inner) => inner.render(buf),
+            Self::CpuCores(inner) => inner.render(buf),
+            Self::DevicesOnline(inner) => inner.render(buf),
+            Self::Disk(inner) => inner.render(buf),
             Self::HistoryChart(inner) => inner.render(buf),
+            Self::Load(inner) => inner.render(buf),
             Self::Memory(inner) => inner.render(buf),
             Self::Miflora(inner) => inner.render(buf),
+            Self::Network(inner) => inner.render(buf),
+            Self::Process(inner) => inner.render(buf),
             Self::Swap(inner) => inner.render(buf),
+            Self::Thermal(inner) => inner.render(buf),
+            Self::Uptime(inner) => inner.render(buf),
         }
     }
 }
@@ -0,0 +1,92 @@
+use std::borrow::Cow;
+
+use another_html_builder::{Body, Buffer};
+
+use crate::component::icon::{Icon, IconKind};
+use crate::component::prelude::Component;
+use crate::helper::fmt;
+
+#[derive(Debug)]
+pub struct InterfaceValues<'a> {
+    pub name: Cow<'a, str>,
+    /// Bytes received per second.
+    pub received: Option<f64>,
+    /// Bytes transmitted per second.
+    pub transmitted: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Card<'a> {
+    interfaces: Vec<InterfaceValues<'a>>,
+}
+
+impl<'a> Card<'a> {
+    pub fn new(mut interfaces: Vec<InterfaceValues<'a>>) -> Self {
+        interfaces.sort_by(|first, second| first.name.cmp(&second.name));
+        Self { interfaces }
+    }
+
+    fn render_rate<'v, W: std::fmt::Write>(
+        buf: Buffer<W, Body<'v>>,
+        icon: IconKind,
+        value: Option<f64>,
+    ) -> Buffer<W, Body<'v>> {
+        buf.node("div")
+            .attr(("class", "flex-row mx-sm"))
+            .content(|buf| {
+                let buf = Icon::new(icon).render(buf);
+                buf.node("label").content(|buf| match value {
+                    // the formatter would pick the smallest prefix for zero
+                    Some(value) if value < 1.0 => buf.text("0 B/s"),
+                    Some(value) => buf.raw(fmt::BYTES_RATE.format(value)),
+                    None => buf.text("-"),
+                })
+            })
+    }
+
+    fn render_interface_row<'v, W: std::fmt::Write>(
+        &self,
+        buf: Buffer<W, Body<'v>>,
+        interface: &InterfaceValues<'a>,
+    ) -> Buffer<W, Body<'v>> {
+        buf.node("div")
+            .attr(("class", "flex-row m-sm mx-md"))
+            .attr(("data-interface", interface.name.as_ref()))
+            .content(|buf| {
+                let buf = buf
+                    .node("div")
+                    .attr(("class", "flex-1"))
+                    .content(|buf| buf.text(interface.name.as_ref()));
+                let buf = Self::render_rate(buf, IconKind::Download, interface.received);
+                Self::render_rate(buf, IconKind::Upload, interface.transmitted)
+            })
+    }
+}
+
+impl<'a> crate::component::prelude::Component for Card<'a> {
+    fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        buf.node("div")
+            .attr((
+                "class",
+                "card network shadow min-w-250px h-150px m-md flex-col",
+            ))
+            .content(|buf| {
+                buf.node("div")
+                    .attr(("class", "card-content flex-1 scroll-y py-md"))
+                    .content(|buf| {
+                        if self.interfaces.is_empty() {
+                            buf.node("p")
+                                .attr(("class", "text-center"))
+                                .content(|buf| buf.text("No interface found"))
+                        } else {
+                            self.interfaces
+                                .iter()
+                                .fold(buf, |buf, item| self.render_interface_row(buf, item))
+                        }
+                    })
+                    .node("div")
+                    .attr(("class", "card-footer"))
+                    .content(|buf| buf.text("Network"))
+            })
+    }
+}
@@ -0,0 +1,101 @@
+use std::borrow::Cow;
+
+use another_html_builder::{Body, Buffer};
+
+use crate::helper::fmt;
+
+#[derive(Debug)]
+pub struct ProcessValues<'a> {
+    pub name: Cow<'a, str>,
+    /// Unknown when the agent didn't report the process yet.
+    pub running: Option<bool>,
+    /// Usage in percent of a core.
+    pub cpu: Option<f64>,
+    /// Resident memory, in bytes.
+    pub memory: Option<f64>,
+    pub restarts: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Card<'a> {
+    processes: Vec<ProcessValues<'a>>,
+}
+
+impl<'a> Card<'a> {
+    pub fn new(mut processes: Vec<ProcessValues<'a>>) -> Self {
+        processes.sort_by(|first, second| first.name.cmp(&second.name));
+        Self { processes }
+    }
+
+    fn render_process_row<'v, W: std::fmt::Write>(
+        &self,
+        buf: Buffer<W, Body<'v>>,
+        process: &ProcessValues<'a>,
+    ) -> Buffer<W, Body<'v>> {
+        let (status, classname) = match process.running {
+            Some(true) => ("up", "flex-row m-sm mx-md"),
+            Some(false) => ("down", "flex-row m-sm mx-md text-error"),
+            None => ("-", "flex-row m-sm mx-md"),
+        };
+        buf.node("div")
+            .attr(("class", classname))
+            .attr(("data-process", process.name.as_ref()))
+            .content(|buf| {
+                buf.node("div")
+                    .attr(("class", "flex-1"))
+                    .content(|buf| buf.text(process.name.as_ref()))
+                    .node("div")
+                    .attr(("class", "mx-sm"))
+                    .content(|buf| match process.cpu {
+                        // the formatter would use a prefix below one percent
+                        Some(value) => buf.text(&format!("{value:.1} %")),
+                        None => buf.text("-"),
+                    })
+                    .node("div")
+                    .attr(("class", "mx-sm"))
+                    .content(|buf| match process.memory {
+                        Some(value) => buf.raw(fmt::BYTES.format(value)),
+                        None => buf.text("-"),
+                    })
+                    .node("div")
+                    .attr(("class", "mx-sm tooltip"))
+                    .content(|buf| {
+                        buf.text(status)
+                            .node("span")
+                            .attr(("class", "tooltip-content"))
+                            .content(|buf| match process.restarts {
+                                Some(value) => buf.raw(value).text(" restart(s)"),
+                                None => buf.text("No restart information"),
+                            })
+                    })
+            })
+    }
+}
+
+impl<'a> crate::component::prelude::Component for Card<'a> {
+    fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        buf.node("div")
+            .attr((
+                "class",
+                "card processes shadow min-w-500px h-150px m-md flex-col",
+            ))
+            .content(|buf| {
+                buf.node("div")
+                    .attr(("class", "card-content flex-1 scroll-y py-md"))
+                    .content(|buf| {
+                        if self.processes.is_empty() {
+                            buf.node("p")
+                                .attr(("class", "text-center"))
+                                .content(|buf| buf.text("No process watched"))
+                        } else {
+                            self.processes
+                                .iter()
+                                .fold(buf, |buf, item| self.render_process_row(buf, item))
+                        }
+                    })
+                    .node("div")
+                    .attr(("class", "card-footer"))
+                    .content(|buf| buf.text("Processes"))
+            })
+    }
+}
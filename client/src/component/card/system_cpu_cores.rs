@@ -0,0 +1,66 @@
+use another_html_builder::{Body, Buffer};
+
+use crate::helper::fmt;
+
+#[derive(Debug, Default)]
+pub struct Card {
+    cores: Vec<(i64, f64)>,
+}
+
+impl Card {
+    /// Takes the usage of each core, in percent, along with the core index.
+    pub fn new(mut cores: Vec<(i64, f64)>) -> Self {
+        cores.sort_by_key(|(index, _)| *index);
+        Self { cores }
+    }
+
+    fn render_core_row<'v, W: std::fmt::Write>(
+        &self,
+        buf: Buffer<W, Body<'v>>,
+        (index, usage): (i64, f64),
+    ) -> Buffer<W, Body<'v>> {
+        buf.node("div")
+            .attr(("class", "flex-row m-sm mx-md"))
+            .attr(("data-core", index))
+            .content(|buf| {
+                buf.node("div")
+                    .attr(("class", "flex-1"))
+                    .content(|buf| buf.text("Core ").raw(index))
+                    .node("div")
+                    .content(|buf| buf.raw(fmt::PERCENTAGE.format(usage)))
+                    .node("progress")
+                    .attr(("value", usage as u64))
+                    .attr(("max", 100))
+                    .attr(("min", 0))
+                    .content(|buf| buf)
+            })
+    }
+}
+
+impl crate::component::prelude::Component for Card {
+    fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        buf.node("div")
+            .attr((
+                "class",
+                "card cpu-cores shadow min-w-250px h-150px m-md flex-col",
+            ))
+            .content(|buf| {
+                buf.node("div")
+                    .attr(("class", "card-content flex-1 scroll-y py-md"))
+                    .content(|buf| {
+                        if self.cores.is_empty() {
+                            buf.node("p")
+                                .attr(("class", "text-center"))
+                                .content(|buf| buf.text(" - "))
+                        } else {
+                            self.cores
+                                .iter()
+                                .fold(buf, |buf, item| self.render_core_row(buf, *item))
+                        }
+                    })
+                    .node("div")
+                    .attr(("class", "card-footer"))
+                    .content(|buf| buf.text("CPU cores"))
+            })
+    }
+}
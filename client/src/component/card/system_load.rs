@@ -0,0 +1,88 @@
+use another_html_builder::{Body, Buffer};
+
+/// Formats a duration in seconds like `3d 4h 12m`.
+fn format_uptime(value: u64) -> String {
+    let days = value / 86400;
+    let hours = (value % 86400) / 3600;
+    let minutes = (value % 3600) / 60;
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Values {
+    pub load_1m: Option<f64>,
+    pub load_5m: Option<f64>,
+    pub load_15m: Option<f64>,
+    /// Time since boot, in seconds.
+    pub uptime: Option<u64>,
+    pub process_count: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct Card {
+    values: Values,
+}
+
+impl Card {
+    pub fn new(values: Values) -> Self {
+        Self { values }
+    }
+}
+
+impl crate::component::prelude::Component for Card {
+    fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        buf.node("div")
+            .attr((
+                "class",
+                "card system-load shadow min-w-250px y-sm m-md flex-col",
+            ))
+            .content(|buf| {
+                buf.node("div")
+                    .attr((
+                        "class",
+                        "card-content flex-1 text-center align-content-center py-md",
+                    ))
+                    .content(|buf| {
+                        let loads = [
+                            self.values.load_1m,
+                            self.values.load_5m,
+                            self.values.load_15m,
+                        ];
+                        buf.node("p")
+                            .attr(("class", "text-xl"))
+                            .content(|buf| {
+                                loads.iter().enumerate().fold(buf, |buf, (index, value)| {
+                                    let buf = if index > 0 { buf.text(" ") } else { buf };
+                                    match value {
+                                        Some(value) => buf.text(&format!("{value:.2}")),
+                                        None => buf.text("-"),
+                                    }
+                                })
+                            })
+                            .node("p")
+                            .content(|buf| {
+                                let buf = buf.text("Up ");
+                                let buf = match self.values.uptime {
+                                    Some(value) => buf.text(&format_uptime(value)),
+                                    None => buf.text("-"),
+                                };
+                                let buf = buf.text(", ");
+                                let buf = match self.values.process_count {
+                                    Some(value) => buf.raw(value),
+                                    None => buf.text("-"),
+                                };
+                                buf.text(" processes")
+                            })
+                    })
+                    .node("div")
+                    .attr(("class", "card-footer"))
+                    .content(|buf| buf.text("Load average"))
+            })
+    }
+}
@@ -0,0 +1,79 @@
+use std::borrow::Cow;
+
+use another_html_builder::{Body, Buffer};
+
+use crate::helper::fmt;
+
+#[derive(Debug)]
+pub struct SensorValue<'a> {
+    pub name: Cow<'a, str>,
+    pub temperature: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct Card<'a> {
+    sensors: Vec<SensorValue<'a>>,
+    max: Option<f64>,
+}
+
+impl<'a> Card<'a> {
+    pub fn new(mut sensors: Vec<SensorValue<'a>>) -> Self {
+        sensors.sort_by(|first, second| first.name.cmp(&second.name));
+        Self { sensors, max: None }
+    }
+
+    /// Highlights the sensors going above the given temperature.
+    pub fn with_max(mut self, max: Option<f64>) -> Self {
+        self.max = max;
+        self
+    }
+
+    fn render_sensor_row<'v, W: std::fmt::Write>(
+        &self,
+        buf: Buffer<W, Body<'v>>,
+        sensor: &SensorValue<'a>,
+    ) -> Buffer<W, Body<'v>> {
+        let classname = match self.max {
+            Some(max) if sensor.temperature > max => "flex-row m-sm mx-md text-error",
+            _ => "flex-row m-sm mx-md",
+        };
+        buf.node("div")
+            .attr(("class", classname))
+            .attr(("data-label", sensor.name.as_ref()))
+            .content(|buf| {
+                buf.node("div")
+                    .attr(("class", "flex-1"))
+                    .content(|buf| buf.text(sensor.name.as_ref()))
+                    .node("div")
+                    .content(|buf| buf.raw(fmt::TEMPERATURE.format(sensor.temperature)))
+            })
+    }
+}
+
+impl<'a> crate::component::prelude::Component for Card<'a> {
+    fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        buf.node("div")
+            .attr((
+                "class",
+                "card thermal shadow min-w-250px h-150px m-md flex-col",
+            ))
+            .content(|buf| {
+                buf.node("div")
+                    .attr(("class", "card-content flex-1 scroll-y py-md"))
+                    .content(|buf| {
+                        if self.sensors.is_empty() {
+                            buf.node("p")
+                                .attr(("class", "text-center"))
+                                .content(|buf| buf.text("No sensor found"))
+                        } else {
+                            self.sensors
+                                .iter()
+                                .fold(buf, |buf, item| self.render_sensor_row(buf, item))
+                        }
+                    })
+                    .node("div")
+                    .attr(("class", "card-footer"))
+                    .content(|buf| buf.text("Temperatures"))
+            })
+    }
+}
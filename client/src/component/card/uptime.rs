@@ -0,0 +1,92 @@
+use std::borrow::Cow;
+
+use another_html_builder::{Body, Buffer};
+
+use crate::view::dashboard::TimePickerDuration;
+
+#[derive(Debug)]
+pub struct TargetValues<'a> {
+    pub name: Cow<'a, str>,
+    /// Unknown when the target wasn't probed yet.
+    pub up: Option<bool>,
+    /// Ratio of the selected duration during which the target was up, between 0 and 1.
+    pub availability: Option<f64>,
+    /// Latest response time, in milliseconds.
+    pub response_time: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct Card<'a> {
+    targets: Vec<TargetValues<'a>>,
+    duration: TimePickerDuration,
+}
+
+impl<'a> Card<'a> {
+    pub fn new(mut targets: Vec<TargetValues<'a>>, duration: TimePickerDuration) -> Self {
+        targets.sort_by(|first, second| first.name.cmp(&second.name));
+        Self { targets, duration }
+    }
+
+    fn render_target_row<'v, W: std::fmt::Write>(
+        &self,
+        buf: Buffer<W, Body<'v>>,
+        target: &TargetValues<'a>,
+    ) -> Buffer<W, Body<'v>> {
+        let (status, classname) = match target.up {
+            Some(true) => ("up", "flex-row m-sm mx-md"),
+            Some(false) => ("down", "flex-row m-sm mx-md text-error"),
+            None => ("-", "flex-row m-sm mx-md"),
+        };
+        buf.node("div")
+            .attr(("class", classname))
+            .attr(("data-target", target.name.as_ref()))
+            .content(|buf| {
+                buf.node("div")
+                    .attr(("class", "flex-1"))
+                    .content(|buf| buf.text(target.name.as_ref()))
+                    .node("div")
+                    .attr(("class", "mx-sm"))
+                    .content(|buf| match target.response_time {
+                        Some(value) => buf.text(&format!("{value:.0} ms")),
+                        None => buf.text("-"),
+                    })
+                    .node("div")
+                    .attr(("class", "mx-sm"))
+                    .content(|buf| match target.availability {
+                        Some(value) => buf.text(&format!("{:.2} %", value * 100.0)),
+                        None => buf.text("-"),
+                    })
+                    .node("div")
+                    .attr(("class", "mx-sm"))
+                    .content(|buf| buf.text(status))
+            })
+    }
+}
+
+impl<'a> crate::component::prelude::Component for Card<'a> {
+    fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        buf.node("div")
+            .attr((
+                "class",
+                "card uptime shadow min-w-500px h-150px m-md flex-col",
+            ))
+            .content(|buf| {
+                buf.node("div")
+                    .attr(("class", "card-content flex-1 scroll-y py-md"))
+                    .content(|buf| {
+                        if self.targets.is_empty() {
+                            buf.node("p")
+                                .attr(("class", "text-center"))
+                                .content(|buf| buf.text("No target probed"))
+                        } else {
+                            self.targets
+                                .iter()
+                                .fold(buf, |buf, item| self.render_target_row(buf, item))
+                        }
+                    })
+                    .node("div")
+                    .attr(("class", "card-footer"))
+                    .content(|buf| buf.text("Uptime over ").text(self.duration.as_value()))
+            })
+    }
+}
@@ -4,9 +4,11 @@ use std::fmt::Write;
 pub enum IconKind {
     Battery,
     Dashboard,
+    Download,
     Sun,
     TemperatureHot,
     Time,
+    Upload,
     Water,
 }
 
@@ -15,9 +17,11 @@ impl IconKind {
         match self {
             Self::Battery => "ri-battery-2-line",
             Self::Dashboard => "ri-dashboard-2-line",
+            Self::Download => "ri-download-line",
             Self::Sun => "ri-sun-line",
             Self::TemperatureHot => "ri-temp-hot-line",
             Self::Time => "ri-time-line",
+            Self::Upload => "ri-upload-line",
             Self::Water => "ri-drop-line",
         }
     }
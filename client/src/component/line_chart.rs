@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::ops::Range;
 
 use another_html_builder::{Body, Buffer};
@@ -10,15 +11,15 @@ fn format_hourly(ts: &u64) -> String {
 
 #[derive(Debug)]
 pub struct Serie<'a> {
-    name: &'a str,
+    name: Cow<'a, str>,
     point_size: u32,
     values: Vec<(u64, f64)>,
 }
 
 impl<'a> Serie<'a> {
-    pub fn new(name: &'a str, values: Vec<(u64, f64)>) -> Self {
+    pub fn new(name: impl Into<Cow<'a, str>>, values: Vec<(u64, f64)>) -> Self {
         Self {
-            name,
+            name: name.into(),
             point_size: 1,
             values,
         }
@@ -120,7 +121,7 @@ impl<'a> LineChart<'a> {
         }
     }
 
-    fn into_svg(&self) -> Result<String, std::io::Error> {
+    fn to_svg(&self) -> Result<String, std::io::Error> {
         use plotters::prelude::*;
 
         let Some(x_range) = self.x_range() else {
@@ -152,14 +153,29 @@ impl<'a> LineChart<'a> {
                 .draw()
                 .map_err(from_chart_error)?;
 
-            for serie in self.series.iter() {
+            for (index, serie) in self.series.iter().enumerate() {
+                // keeps the historical red for single serie charts
+                let color: RGBAColor = if index == 0 {
+                    RED.to_rgba()
+                } else {
+                    Palette99::pick(index).to_rgba()
+                };
                 chart
                     .draw_series(
-                        LineSeries::new(serie.values.iter().copied(), &RED)
+                        LineSeries::new(serie.values.iter().copied(), color)
                             .point_size(serie.point_size),
                     )
                     .map_err(from_chart_error)?
-                    .label(serie.name);
+                    .label(serie.name.as_ref())
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], color));
+            }
+            if self.series.len() > 1 {
+                chart
+                    .configure_series_labels()
+                    .background_style(WHITE.mix(0.8))
+                    .border_style(BLACK)
+                    .draw()
+                    .map_err(from_chart_error)?;
             }
         }
 
@@ -169,7 +185,7 @@ impl<'a> LineChart<'a> {
 
 impl<'a> crate::component::prelude::Component for LineChart<'a> {
     fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
-        match self.into_svg() {
+        match self.to_svg() {
             Ok(svg) => buf.raw(svg),
             Err(err) => {
                 tracing::warn!(message = "unable to generate svg", error = %err);
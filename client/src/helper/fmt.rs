@@ -15,3 +15,7 @@ pub(crate) static BYTES: LazyLock<human_number::Formatter<'static>> = LazyLock::
         .with_unit("B")
         .with_decimals(1)
 });
+pub(crate) static BYTES_RATE: LazyLock<Formatter<'static>> =
+    LazyLock::new(|| Formatter::si().with_unit("B/s").with_decimals(1));
+pub(crate) static NUMBER: LazyLock<Formatter<'static>> =
+    LazyLock::new(|| Formatter::si().with_decimals(2));
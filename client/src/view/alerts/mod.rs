@@ -0,0 +1,226 @@
+use std::borrow::Cow;
+
+use another_html_builder::{Body, Buffer};
+
+use crate::component::helper::format_datetime;
+use crate::component::prelude::Component;
+use crate::helper::fmt;
+
+fn render_datetime<'v, W: std::fmt::Write>(
+    buf: Buffer<W, Body<'v>>,
+    timestamp: u64,
+) -> Buffer<W, Body<'v>> {
+    match format_datetime(timestamp) {
+        Some(dt) => buf.raw(dt),
+        None => buf.text("-"),
+    }
+}
+
+fn render_value<'v, W: std::fmt::Write>(
+    buf: Buffer<W, Body<'v>>,
+    value: Option<f64>,
+) -> Buffer<W, Body<'v>> {
+    match value {
+        Some(value) => buf.raw(fmt::NUMBER.format(value)),
+        None => buf.text("-"),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Firing,
+    Resolved,
+}
+
+impl Status {
+    const fn label(&self) -> &'static str {
+        match self {
+            Self::Firing => "Firing",
+            Self::Resolved => "Resolved",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Transition {
+    pub status: Status,
+    pub value: Option<f64>,
+    pub timestamp: u64,
+}
+
+impl Component for Transition {
+    fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        buf.node("div")
+            .attr(("class", "flex-row my-sm"))
+            .attr(("data-status", self.status.label()))
+            .content(|buf| {
+                let buf = buf
+                    .node("label")
+                    .attr(("class", "flex-1"))
+                    .content(|buf| render_datetime(buf, self.timestamp));
+                let buf = buf
+                    .node("label")
+                    .attr(("class", "mx-md"))
+                    .content(|buf| buf.text(self.status.label()));
+                buf.node("label")
+                    .content(|buf| render_value(buf, self.value))
+            })
+    }
+}
+
+/// State of a rule over a single serie.
+#[derive(Debug)]
+pub struct Alert<'a> {
+    /// Metric name and tags of the serie.
+    pub target: Cow<'a, str>,
+    pub status: Status,
+    /// When the condition started to be met.
+    pub since: u64,
+    pub value: Option<f64>,
+    /// Latest transitions, most recent first.
+    pub transitions: Vec<Transition>,
+}
+
+impl<'a> Alert<'a> {
+    fn render_row<'v, W, F>(buf: Buffer<W, Body<'v>>, name: &str, content: F) -> Buffer<W, Body<'v>>
+    where
+        W: std::fmt::Write,
+        F: for<'x> FnOnce(Buffer<W, Body<'x>>) -> Buffer<W, Body<'x>>,
+    {
+        buf.node("div")
+            .attr(("class", "flex-row mx-md my-sm"))
+            .attr(("data-label", name))
+            .content(|buf| {
+                let buf = buf
+                    .node("label")
+                    .attr(("class", "flex-1"))
+                    .content(|buf| buf.text(name));
+                buf.node("label").content(content)
+            })
+    }
+}
+
+impl<'a> Component for Alert<'a> {
+    fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        let classname = match self.status {
+            Status::Firing => "card m-md min-w-250px bg-error",
+            Status::Resolved => "card m-md min-w-250px",
+        };
+        buf.node("div")
+            .attr(("class", classname))
+            .attr(("data-status", self.status.label()))
+            .content(|buf| {
+                let buf = buf
+                    .node("div")
+                    .attr(("class", "card-content py-md"))
+                    .content(|buf| {
+                        let buf = buf
+                            .node("div")
+                            .attr(("class", "mx-md my-sm text-lg"))
+                            .content(|buf| buf.text(self.target.as_ref()));
+                        let buf =
+                            Self::render_row(buf, "State", |buf| buf.text(self.status.label()));
+                        let buf =
+                            Self::render_row(buf, "Since", |buf| render_datetime(buf, self.since));
+                        Self::render_row(buf, "Value", |buf| render_value(buf, self.value))
+                    });
+                buf.cond(!self.transitions.is_empty(), |buf| {
+                    buf.node("div")
+                        .attr(("class", "card-footer"))
+                        .content(|buf| {
+                            self.transitions
+                                .iter()
+                                .fold(buf, |buf, transition| transition.render(buf))
+                        })
+                })
+            })
+    }
+}
+
+#[derive(Debug)]
+pub struct Rule<'a> {
+    pub name: Cow<'a, str>,
+    /// Human readable condition, like `below 20`.
+    pub condition: Cow<'a, str>,
+    pub alerts: Vec<Alert<'a>>,
+}
+
+impl<'a> Component for Rule<'a> {
+    fn render<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        buf.node("section").content(|buf| {
+            let buf = buf.node("h3").attr(("class", "mt-xl")).content(|buf| {
+                buf.text(self.name.as_ref())
+                    .text(" (")
+                    .text(self.condition.as_ref())
+                    .text(")")
+            });
+            buf.node("div")
+                .attr(("class", "flex-row flex-wrap"))
+                .content(|buf| {
+                    if self.alerts.is_empty() {
+                        buf.node("div")
+                            .attr(("class", "card m-md min-w-250px bg-success"))
+                            .attr(("data-status", "Ok"))
+                            .content(|buf| {
+                                buf.node("div")
+                                    .attr(("class", "card-content mx-md py-md"))
+                                    .content(|buf| buf.text("No alert"))
+                            })
+                    } else {
+                        self.alerts.iter().fold(buf, |buf, alert| alert.render(buf))
+                    }
+                })
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct View<'a> {
+    rules: Vec<Rule<'a>>,
+}
+
+impl<'a> View<'a> {
+    pub fn new(rules: Vec<Rule<'a>>) -> Self {
+        Self { rules }
+    }
+
+    pub fn with_rule(mut self, rule: Rule<'a>) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    #[inline]
+    fn render_head<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        crate::component::head::Head::new("Alerts").render(buf)
+    }
+
+    fn render_body<'v, W: std::fmt::Write>(&self, buf: Buffer<W, Body<'v>>) -> Buffer<W, Body<'v>> {
+        buf.node("body").content(|buf| {
+            let buf = crate::component::header::Header::<()>::new("Alerts").render(buf);
+            buf.node("main").content(|buf| {
+                if self.rules.is_empty() {
+                    buf.node("p")
+                        .attr(("class", "mt-xl"))
+                        .content(|buf| buf.text("No alerting rule defined."))
+                } else {
+                    self.rules.iter().fold(buf, |buf, rule| rule.render(buf))
+                }
+            })
+        })
+    }
+}
+
+impl<'a> super::prelude::View for View<'a> {
+    fn render(self) -> String {
+        another_html_builder::Buffer::default()
+            .doctype()
+            .node("html")
+            .attr(("lang", "en"))
+            .content(|buf| {
+                let buf = self.render_head(buf);
+                let buf = self.render_body(buf);
+                buf
+            })
+            .into_inner()
+    }
+}
@@ -1,3 +1,4 @@
+pub mod alerts;
 pub mod dashboard;
 pub mod error;
 pub mod prelude;
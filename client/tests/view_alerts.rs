@@ -0,0 +1,52 @@
+use chezmoi_client::view::alerts::{Alert, Rule, Status, Transition, View};
+
+mod helper;
+
+#[test]
+fn without_rules() {
+    helper::write("alerts-without-rules.html", View::default());
+}
+
+#[test]
+fn with_firing_and_resolved_alerts() {
+    let html = View::default()
+        .with_rule(Rule {
+            name: "Ficus needs water".into(),
+            condition: "below 20".into(),
+            alerts: vec![Alert {
+                target: "miflora.moisture address=00:00:00:00:00".into(),
+                status: Status::Firing,
+                since: 86400,
+                value: Some(12.5),
+                transitions: vec![
+                    Transition {
+                        status: Status::Firing,
+                        value: Some(12.5),
+                        timestamp: 86400,
+                    },
+                    Transition {
+                        status: Status::Resolved,
+                        value: Some(25.0),
+                        timestamp: 3600,
+                    },
+                ],
+            }],
+        })
+        .with_rule(Rule {
+            name: "Ficus too hot".into(),
+            condition: "above 30".into(),
+            alerts: vec![Alert {
+                target: "miflora.temperature address=00:00:00:00:00".into(),
+                status: Status::Resolved,
+                since: 3600,
+                value: Some(22.0),
+                transitions: Vec::new(),
+            }],
+        })
+        .with_rule(Rule {
+            name: "Cactus too wet".into(),
+            condition: "above 60".into(),
+            alerts: Vec::new(),
+        });
+    helper::write("alerts-with-rules.html", html);
+}
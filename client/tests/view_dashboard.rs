@@ -41,57 +41,57 @@ fn with_bluetooth_devices() {
             .with_section(
                 Section::new("Many devices").with_card(AnyCard::BluetoothDevices(Card::new(vec![
                     DeviceValues {
-                        address: "00:00:00:00:00",
-                        name: Some("Foo"),
+                        address: "00:00:00:00:00".into(),
+                        name: Some("Foo".into()),
                         tx_power: 80.0,
-                        battery: None,
-                        timestamp: 0,
+                        battery: Some(85.0),
+                        timestamp: 1_729_000_000,
                     },
                     DeviceValues {
-                        address: "00:00:00:00:01",
-                        name: Some("Baz"),
+                        address: "00:00:00:00:01".into(),
+                        name: Some("Baz".into()),
                         tx_power: 10.0,
                         battery: None,
                         timestamp: 0,
                     },
                     DeviceValues {
-                        address: "00:00:00:00:02",
-                        name: Some("Hello"),
+                        address: "00:00:00:00:02".into(),
+                        name: Some("Hello".into()),
                         tx_power: 100.0,
-                        battery: None,
-                        timestamp: 0,
+                        battery: Some(12.0),
+                        timestamp: 1_729_003_600,
                     },
                     DeviceValues {
-                        address: "00:00:00:00:03",
-                        name: Some("World"),
+                        address: "00:00:00:00:03".into(),
+                        name: Some("World".into()),
                         tx_power: 90.0,
                         battery: None,
                         timestamp: 0,
                     },
                     DeviceValues {
-                        address: "00:00:00:00:04",
-                        name: Some("Asterix"),
+                        address: "00:00:00:00:04".into(),
+                        name: Some("Asterix".into()),
                         tx_power: 83.0,
                         battery: None,
                         timestamp: 0,
                     },
                     DeviceValues {
-                        address: "00:00:00:00:05",
-                        name: Some("Obelix"),
+                        address: "00:00:00:00:05".into(),
+                        name: Some("Obelix".into()),
                         tx_power: 83.0,
                         battery: None,
                         timestamp: 0,
                     },
                     DeviceValues {
-                        address: "00:00:00:00:06",
-                        name: Some("Panoramix"),
+                        address: "00:00:00:00:06".into(),
+                        name: Some("Panoramix".into()),
                         tx_power: 56.0,
                         battery: None,
                         timestamp: 0,
                     },
                     DeviceValues {
-                        address: "00:00:00:00:07",
-                        name: Some("Bar"),
+                        address: "00:00:00:00:07".into(),
+                        name: Some("Bar".into()),
                         tx_power: 70.0,
                         battery: None,
                         timestamp: 0,
@@ -103,23 +103,147 @@ fn with_bluetooth_devices() {
 
 #[test]
 fn with_system() {
-    use chezmoi_client::component::card::{system_cpu, system_memory, system_swap, AnyCard};
+    use chezmoi_client::component::card::{
+        disk, system_cpu, system_cpu_cores, system_load, system_memory, system_swap, AnyCard,
+    };
 
     helper::write(
         "with-system-cards.html",
-        View::new(Vec::new(), TimePickerDuration::OneWeek).with_section(
-            Section::new("Simple")
-                .with_card(AnyCard::Memory(system_memory::Card::new(
-                    Some(1024.0 * 1024.0 * 1024.0 * 64.0),
-                    Some(1024.0 * 1024.0 * 1024.0 * 2.2),
-                )))
-                .with_card(AnyCard::Swap(system_swap::Card::new(
-                    Some(1024.0 * 1024.0 * 1024.0 * 64.0),
-                    Some(1024.0 * 1024.0 * 1024.0 * 2.2),
-                )))
-                .with_card(AnyCard::Cpu(system_cpu::Card::new(Some(68.0))))
-                .with_card(AnyCard::Cpu(system_cpu::Card::new(None))),
-        ),
+        View::new(Vec::new(), TimePickerDuration::OneWeek)
+            .with_section(
+                Section::new("Simple")
+                    .with_card(AnyCard::Memory(system_memory::Card::new(
+                        Some(1024.0 * 1024.0 * 1024.0 * 64.0),
+                        Some(1024.0 * 1024.0 * 1024.0 * 2.2),
+                    )))
+                    .with_card(AnyCard::Swap(system_swap::Card::new(
+                        Some(1024.0 * 1024.0 * 1024.0 * 64.0),
+                        Some(1024.0 * 1024.0 * 1024.0 * 2.2),
+                    )))
+                    .with_card(AnyCard::Cpu(system_cpu::Card::new(Some(68.0))))
+                    .with_card(AnyCard::Cpu(system_cpu::Card::new(None)))
+                    .with_card(AnyCard::CpuCores(system_cpu_cores::Card::new(vec![
+                        (1, 12.5),
+                        (0, 100.0),
+                        (3, 0.0),
+                        (2, 42.0),
+                    ])))
+                    .with_card(AnyCard::Load(system_load::Card::new(system_load::Values {
+                        load_1m: Some(3.42),
+                        load_5m: Some(1.2),
+                        load_15m: Some(0.8),
+                        uptime: Some(86400 * 3 + 3600 * 4 + 60 * 12),
+                        process_count: Some(231),
+                    }))),
+            )
+            .with_section(
+                Section::new("Without values")
+                    .with_card(AnyCard::CpuCores(system_cpu_cores::Card::new(Vec::new())))
+                    .with_card(AnyCard::Load(system_load::Card::new(Default::default())))
+                    .with_card(AnyCard::Disk(disk::Card::new("/data", None, None))),
+            ),
+    );
+}
+
+#[test]
+fn with_network() {
+    use chezmoi_client::component::card::network::{Card, InterfaceValues};
+
+    helper::write(
+        "with-network-cards.html",
+        View::new(Vec::new(), TimePickerDuration::OneWeek)
+            .with_section(
+                Section::new("No interface").with_card(AnyCard::Network(Card::new(Vec::new()))),
+            )
+            .with_section(
+                Section::new("Many interfaces").with_card(AnyCard::Network(Card::new(vec![
+                    InterfaceValues {
+                        name: "wlan0".into(),
+                        received: None,
+                        transmitted: None,
+                    },
+                    InterfaceValues {
+                        name: "wg0".into(),
+                        received: Some(0.0),
+                        transmitted: Some(0.0),
+                    },
+                    InterfaceValues {
+                        name: "eth0".into(),
+                        received: Some(12.5 * 1000.0 * 1000.0),
+                        transmitted: Some(820.0 * 1000.0),
+                    },
+                ]))),
+            ),
+    );
+}
+
+#[test]
+fn with_process() {
+    use chezmoi_client::component::card::process::{Card, ProcessValues};
+
+    helper::write(
+        "with-process-cards.html",
+        View::new(Vec::new(), TimePickerDuration::OneWeek)
+            .with_section(
+                Section::new("No process").with_card(AnyCard::Process(Card::new(Vec::new()))),
+            )
+            .with_section(
+                Section::new("Many processes").with_card(AnyCard::Process(Card::new(vec![
+                    ProcessValues {
+                        name: "mosquitto".into(),
+                        running: Some(true),
+                        cpu: Some(0.4),
+                        memory: Some(8.0 * 1024.0 * 1024.0),
+                        restarts: Some(0),
+                    },
+                    ProcessValues {
+                        name: "home-assistant".into(),
+                        running: Some(false),
+                        cpu: None,
+                        memory: None,
+                        restarts: Some(3),
+                    },
+                    ProcessValues {
+                        name: "zigbee2mqtt".into(),
+                        running: None,
+                        cpu: None,
+                        memory: None,
+                        restarts: None,
+                    },
+                ]))),
+            ),
+    );
+}
+
+#[test]
+fn with_thermal() {
+    use chezmoi_client::component::card::thermal::{Card, SensorValue};
+
+    helper::write(
+        "with-thermal-cards.html",
+        View::new(Vec::new(), TimePickerDuration::OneWeek)
+            .with_section(
+                Section::new("No sensor").with_card(AnyCard::Thermal(Card::new(Vec::new()))),
+            )
+            .with_section(
+                Section::new("Many sensors").with_card(AnyCard::Thermal(
+                    Card::new(vec![
+                        SensorValue {
+                            name: "cpu-thermal".into(),
+                            temperature: 52.1,
+                        },
+                        SensorValue {
+                            name: "nvme Composite".into(),
+                            temperature: 38.9,
+                        },
+                        SensorValue {
+                            name: format!("{} {}", "coretemp", "Core 0").into(),
+                            temperature: 81.0,
+                        },
+                    ])
+                    .with_max(Some(80.0)),
+                )),
+            ),
     );
 }
 
@@ -310,3 +434,81 @@ fn with_history_chart_cards() {
             ),
     );
 }
+
+#[test]
+fn with_uptime() {
+    use chezmoi_client::component::card::uptime::{Card, TargetValues};
+
+    helper::write(
+        "with-uptime-cards.html",
+        View::new(Vec::new(), TimePickerDuration::OneDay)
+            .with_section(
+                Section::new("No target").with_card(AnyCard::Uptime(Card::new(
+                    Vec::new(),
+                    TimePickerDuration::OneDay,
+                ))),
+            )
+            .with_section(
+                Section::new("Many targets").with_card(AnyCard::Uptime(Card::new(
+                    vec![
+                        TargetValues {
+                            name: "nextcloud".into(),
+                            up: Some(true),
+                            availability: Some(0.9995),
+                            response_time: Some(123.4),
+                        },
+                        TargetValues {
+                            name: "router".into(),
+                            up: Some(false),
+                            availability: Some(0.5),
+                            response_time: None,
+                        },
+                        TargetValues {
+                            name: "printer".into(),
+                            up: None,
+                            availability: None,
+                            response_time: None,
+                        },
+                    ],
+                    TimePickerDuration::OneDay,
+                ))),
+            ),
+    );
+}
+
+#[test]
+fn with_devices_online() {
+    use chezmoi_client::component::card::devices_online::{Card, DeviceValues};
+
+    helper::write(
+        "with-devices-online-cards.html",
+        View::new(Vec::new(), TimePickerDuration::OneWeek)
+            .with_section(
+                Section::new("No device").with_card(AnyCard::DevicesOnline(Card::new(Vec::new()))),
+            )
+            .with_section(
+                Section::new("Many devices").with_card(AnyCard::DevicesOnline(Card::new(vec![
+                    DeviceValues {
+                        name: "alice-phone".into(),
+                        reachable: Some(false),
+                        latency: None,
+                    },
+                    DeviceValues {
+                        name: "bob-phone".into(),
+                        reachable: Some(true),
+                        latency: Some(42.3),
+                    },
+                    DeviceValues {
+                        name: "nas".into(),
+                        reachable: Some(true),
+                        latency: Some(0.4),
+                    },
+                    DeviceValues {
+                        name: "printer".into(),
+                        reachable: None,
+                        latency: None,
+                    },
+                ]))),
+            ),
+    );
+}
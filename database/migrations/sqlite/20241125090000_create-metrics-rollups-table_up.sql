@@ -0,0 +1,12 @@
+create table metrics_rollups (
+    timestamp datetime not null,
+    duration integer not null,
+    name text not null,
+    tags jsonb not null,
+    type text not null,
+    -- numeric affinity keeps the count values as integers
+    min numeric not null,
+    max numeric not null,
+    sum numeric not null,
+    count integer not null
+);
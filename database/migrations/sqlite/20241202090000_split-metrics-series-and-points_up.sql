@@ -0,0 +1,75 @@
+-- a serie is identified by its name and its tags, sorted by key
+create table metric_series (
+    id integer primary key,
+    name text not null,
+    tags jsonb not null,
+    unique (name, tags)
+);
+
+create table metric_points (
+    series_id integer not null references metric_series (id) on delete cascade,
+    timestamp datetime not null,
+    value jsonb not null,
+    primary key (series_id, timestamp)
+) without rowid;
+
+create index metric_points_timestamp_idx on metric_points (timestamp);
+
+create table metric_rollups (
+    series_id integer not null references metric_series (id) on delete cascade,
+    timestamp datetime not null,
+    duration integer not null,
+    type text not null,
+    -- numeric affinity keeps the count values as integers
+    min numeric not null,
+    max numeric not null,
+    sum numeric not null,
+    count integer not null
+);
+
+create index metric_rollups_series_timestamp_idx on metric_rollups (series_id, timestamp);
+create index metric_rollups_timestamp_idx on metric_rollups (timestamp);
+
+-- moving existing data
+alter table metrics add column canonical_tags text;
+update metrics set canonical_tags = (
+    select json_group_object(
+        key,
+        case type when 'true' then json('true') when 'false' then json('false') else value end
+        order by key
+    )
+    from json_each(metrics.tags)
+);
+
+alter table metrics_rollups add column canonical_tags text;
+update metrics_rollups set canonical_tags = (
+    select json_group_object(
+        key,
+        case type when 'true' then json('true') when 'false' then json('false') else value end
+        order by key
+    )
+    from json_each(metrics_rollups.tags)
+);
+
+insert into metric_series (name, tags)
+select name, canonical_tags from metrics
+union
+select name, canonical_tags from metrics_rollups;
+
+-- the latest value wins when several have been stored at the same time
+insert into metric_points (series_id, timestamp, value)
+select metric_series.id, metrics.timestamp, metrics.value
+from metrics
+join metric_series on metric_series.name = metrics.name and metric_series.tags = metrics.canonical_tags
+where true
+order by metrics.rowid
+on conflict (series_id, timestamp) do update set value = excluded.value;
+
+insert into metric_rollups (series_id, timestamp, duration, type, min, max, sum, count)
+select metric_series.id, metrics_rollups.timestamp, metrics_rollups.duration, metrics_rollups.type,
+    metrics_rollups.min, metrics_rollups.max, metrics_rollups.sum, metrics_rollups.count
+from metrics_rollups
+join metric_series on metric_series.name = metrics_rollups.name and metric_series.tags = metrics_rollups.canonical_tags;
+
+drop table metrics;
+drop table metrics_rollups;
@@ -0,0 +1,14 @@
+create table alert_transitions (
+    id integer primary key,
+    rule text not null,
+    name text not null,
+    tags jsonb not null,
+    status text not null,
+    value real,
+    since integer not null,
+    timestamp integer not null
+);
+
+create index alert_transitions_rule_idx on alert_transitions (rule, name, tags, timestamp);
+
+create index alert_transitions_timestamp_idx on alert_transitions (timestamp);
@@ -0,0 +1,33 @@
+use super::AlertTransition;
+
+pub struct Command<'a>(&'a AlertTransition);
+
+impl<'a> Command<'a> {
+    #[inline]
+    pub fn new(transition: &'a AlertTransition) -> Self {
+        Self(transition)
+    }
+
+    pub async fn execute<'c, E: sqlx::Executor<'c, Database = sqlx::Sqlite>>(
+        self,
+        executor: E,
+    ) -> sqlx::Result<u64> {
+        let tags = self
+            .0
+            .header
+            .tags
+            .to_canonical_json()
+            .map_err(|err| sqlx::Error::Encode(Box::new(err)))?;
+        let res = sqlx::query("insert into alert_transitions (rule, name, tags, status, value, since, timestamp) values ($1, $2, $3, $4, $5, $6, $7)")
+            .bind(self.0.rule.as_ref())
+            .bind(self.0.header.name.as_ref())
+            .bind(tags)
+            .bind(self.0.status.as_str())
+            .bind(self.0.value)
+            .bind(self.0.since as i64)
+            .bind(self.0.timestamp as i64)
+            .execute(executor)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
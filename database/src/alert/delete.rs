@@ -0,0 +1,85 @@
+/// Removes the transitions older than `before`.
+///
+/// The latest transition of an alert still firing is kept, as it gives its current state.
+pub struct Command {
+    before: u64,
+}
+
+impl Command {
+    #[inline]
+    pub fn new(before: u64) -> Self {
+        Self { before }
+    }
+
+    pub async fn execute<'c, E: sqlx::Executor<'c, Database = sqlx::Sqlite>>(
+        self,
+        executor: E,
+    ) -> sqlx::Result<u64> {
+        let res = sqlx::query(
+            "delete from alert_transitions
+            where timestamp < $1
+            and id not in (
+                select id from (
+                    select id, status,
+                        row_number() over (partition by rule, name, tags order by timestamp desc, id desc) as idx
+                    from alert_transitions
+                )
+                where idx = 1 and status = 'firing'
+            )",
+        )
+        .bind(self.before as i64)
+        .execute(executor)
+        .await?;
+        Ok(res.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alert::{AlertStatus, AlertTransition};
+    use crate::metrics::MetricHeader;
+
+    fn transition(status: AlertStatus, header: &MetricHeader, timestamp: u64) -> AlertTransition {
+        AlertTransition {
+            rule: "plant".into(),
+            header: header.clone(),
+            status,
+            value: Some(10.0),
+            since: 0,
+            timestamp,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_keep_latest_firing_transitions() {
+        let db = crate::Client::test().await;
+
+        let first = MetricHeader::new("miflora.moisture").with_tag("address", "00:00");
+        let second = MetricHeader::new("miflora.moisture").with_tag("address", "00:01");
+        for item in [
+            transition(AlertStatus::Firing, &first, 10),
+            transition(AlertStatus::Resolved, &first, 20),
+            transition(AlertStatus::Firing, &first, 200),
+            transition(AlertStatus::Firing, &second, 10),
+        ] {
+            crate::alert::create::Command::new(&item)
+                .execute(db.as_ref())
+                .await
+                .unwrap();
+        }
+
+        let count = super::Command::new(100).execute(db.as_ref()).await.unwrap();
+        assert_eq!(count, 2);
+
+        let found = crate::alert::find_latest::Command
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].header, first);
+        assert_eq!(found[0].timestamp, 200);
+        // still firing since then
+        assert_eq!(found[1].header, second);
+        assert_eq!(found[1].status, AlertStatus::Firing);
+    }
+}
@@ -0,0 +1,68 @@
+use super::AlertTransition;
+
+/// Finds the latest transition of every alert, giving their current state.
+#[derive(Default)]
+pub struct Command;
+
+impl Command {
+    pub async fn execute<'c, E: sqlx::Executor<'c, Database = sqlx::Sqlite>>(
+        self,
+        executor: E,
+    ) -> sqlx::Result<Vec<AlertTransition>> {
+        sqlx::query_as(
+            "with transitions as (
+                select rule, name, tags, status, value, since, timestamp,
+                    row_number() over (partition by rule, name, tags order by timestamp desc, id desc) as idx
+                from alert_transitions
+            )
+            select rule, name, tags, status, value, since, timestamp
+            from transitions
+            where idx = 1
+            order by rule, timestamp desc",
+        )
+        .fetch_all(executor)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alert::{AlertStatus, AlertTransition};
+    use crate::metrics::MetricHeader;
+
+    fn transition(status: AlertStatus, header: &MetricHeader, timestamp: u64) -> AlertTransition {
+        AlertTransition {
+            rule: "plant".into(),
+            header: header.clone(),
+            status,
+            value: Some(10.0),
+            since: 0,
+            timestamp,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_find_current_state_of_each_alert() {
+        let db = crate::Client::test().await;
+
+        let first = MetricHeader::new("miflora.moisture").with_tag("address", "00:00");
+        let second = MetricHeader::new("miflora.moisture").with_tag("address", "00:01");
+        for item in [
+            transition(AlertStatus::Firing, &first, 10),
+            transition(AlertStatus::Resolved, &first, 20),
+            transition(AlertStatus::Firing, &second, 15),
+        ] {
+            crate::alert::create::Command::new(&item)
+                .execute(db.as_ref())
+                .await
+                .unwrap();
+        }
+
+        let found = super::Command.execute(db.as_ref()).await.unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].header, first);
+        assert_eq!(found[0].status, AlertStatus::Resolved);
+        assert_eq!(found[1].header, second);
+        assert_eq!(found[1].status, AlertStatus::Firing);
+    }
+}
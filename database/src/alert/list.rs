@@ -0,0 +1,28 @@
+use super::AlertTransition;
+
+/// Lists the transitions that happened since the given timestamp, most recent first.
+pub struct Command {
+    since: u64,
+    limit: Option<usize>,
+}
+
+impl Command {
+    pub fn new(since: u64, limit: Option<usize>) -> Self {
+        Self { since, limit }
+    }
+
+    pub async fn execute<'c, E: sqlx::Executor<'c, Database = sqlx::Sqlite>>(
+        self,
+        executor: E,
+    ) -> sqlx::Result<Vec<AlertTransition>> {
+        let mut qb = sqlx::QueryBuilder::new(
+            "select rule, name, tags, status, value, since, timestamp from alert_transitions",
+        );
+        qb.push(" where timestamp >= ").push_bind(self.since as i64);
+        qb.push(" order by timestamp desc, id desc");
+        if let Some(limit) = self.limit {
+            qb.push(" limit ").push_bind(limit as i64);
+        }
+        qb.build_query_as().fetch_all(executor).await
+    }
+}
@@ -0,0 +1,79 @@
+//! Transitions of the alerts between the firing and resolved states.
+
+use std::borrow::Cow;
+
+use sqlx::types::Json;
+
+use crate::metrics::{MetricHeader, MetricTags};
+
+pub mod create;
+pub mod delete;
+pub mod find_latest;
+pub mod list;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlertStatus {
+    Firing,
+    Resolved,
+}
+
+impl AlertStatus {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Firing => "firing",
+            Self::Resolved => "resolved",
+        }
+    }
+}
+
+impl std::str::FromStr for AlertStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "firing" => Ok(Self::Firing),
+            "resolved" => Ok(Self::Resolved),
+            other => Err(format!("invalid alert status {other:?}")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct AlertTransition {
+    pub rule: Cow<'static, str>,
+    #[serde(flatten)]
+    pub header: MetricHeader,
+    pub status: AlertStatus,
+    /// Value of the metric that triggered the transition, if any.
+    pub value: Option<f64>,
+    /// When the condition started to be met.
+    pub since: u64,
+    pub timestamp: u64,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for AlertTransition {
+    fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        use sqlx::Row;
+
+        let rule: String = row.try_get(0)?;
+        let name: String = row.try_get(1)?;
+        let Json(tags): Json<MetricTags> = row.try_get(2)?;
+        let status: String = row.try_get(3)?;
+        let status = status
+            .parse()
+            .map_err(|err: String| sqlx::Error::Decode(err.into()))?;
+
+        Ok(Self {
+            rule: rule.into(),
+            header: MetricHeader {
+                name: name.into(),
+                tags,
+            },
+            status,
+            value: row.try_get(4)?,
+            since: row.try_get(5)?,
+            timestamp: row.try_get(6)?,
+        })
+    }
+}
@@ -1,5 +1,7 @@
+pub mod alert;
 pub mod helper;
 pub mod metrics;
+pub mod retention;
 
 use std::borrow::Cow;
 
@@ -19,11 +21,17 @@ fn default_url() -> Cow<'static, str> {
 pub struct Config {
     #[serde(default = "default_url")]
     url: Cow<'static, str>,
+    /// When not provided, the metrics are kept forever.
+    #[serde(default)]
+    retention: Option<retention::Config>,
 }
 
 impl Default for Config {
     fn default() -> Self {
-        Self { url: default_url() }
+        Self {
+            url: default_url(),
+            retention: None,
+        }
     }
 }
 
@@ -31,16 +39,26 @@ impl Config {
     pub fn memory() -> Self {
         Self {
             url: ":memory:".into(),
+            retention: None,
         }
     }
 
     pub fn new(url: impl Into<Cow<'static, str>>) -> Self {
-        Self { url: url.into() }
+        Self {
+            url: url.into(),
+            retention: None,
+        }
+    }
+
+    pub fn with_retention(mut self, retention: retention::Config) -> Self {
+        self.retention = Some(retention);
+        self
     }
 
     pub fn from_env() -> anyhow::Result<Self> {
         Ok(Self {
             url: from_env_or("DATABASE_URL", ":memory:"),
+            retention: None,
         })
     }
 
@@ -57,13 +75,17 @@ impl Config {
             .connect_with(opts)
             .await
             .context("building connection pool")?;
-        Ok(crate::Client { inner })
+        Ok(crate::Client {
+            inner,
+            retention: self.retention,
+        })
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct Client {
     inner: sqlx::Pool<sqlx::Sqlite>,
+    retention: Option<retention::Config>,
 }
 
 impl AsRef<sqlx::Pool<sqlx::Sqlite>> for Client {
@@ -81,6 +103,13 @@ impl Client {
     pub async fn upgrade(&self) -> Result<(), sqlx::migrate::MigrateError> {
         MIGRATOR.run(&self.inner).await
     }
+
+    /// Periodically applies the retention policy, returns immediately when none is configured.
+    pub async fn run_retention(self) {
+        if let Some(retention) = self.retention {
+            retention.run(self).await;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -91,3 +120,114 @@ impl Client {
         client
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::migrate::Migrator;
+
+    /// First version storing the metrics in series and points.
+    const SPLIT_VERSION: i64 = 20241202090000;
+
+    #[tokio::test]
+    async fn should_move_metrics_into_series_and_points() {
+        let client = crate::Config::memory().build().await.unwrap();
+        let before = Migrator {
+            migrations: super::MIGRATOR
+                .iter()
+                .filter(|migration| migration.version < SPLIT_VERSION)
+                .cloned()
+                .collect::<Vec<_>>()
+                .into(),
+            ..Migrator::DEFAULT
+        };
+        before.run(client.as_ref()).await.unwrap();
+
+        sqlx::query(
+            r#"insert into metrics (timestamp, name, tags, value) values
+            (10, 'foo', '{"host":"rpi","address":"00:00"}', '{"type":"count","value":1}'),
+            (20, 'foo', '{"address":"00:00","host":"rpi"}', '{"type":"count","value":2}'),
+            (20, 'foo', '{"host":"rpi","address":"00:00"}', '{"type":"count","value":3}'),
+            (10, 'bar', '{"online":true}', '{"type":"bool","value":true}')"#,
+        )
+        .execute(client.as_ref())
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"insert into metrics_rollups (timestamp, duration, name, tags, type, min, max, sum, count) values
+            (0, 10, 'foo', '{"host":"rpi","address":"00:00"}', 'count', 1, 1, 1, 1),
+            (0, 10, 'baz', '{}', 'gauge', 1.5, 2.5, 4.0, 2)"#,
+        )
+        .execute(client.as_ref())
+        .await
+        .unwrap();
+
+        client.upgrade().await.unwrap();
+
+        let series: Vec<(String, String)> =
+            sqlx::query_as("select name, tags from metric_series order by name")
+                .fetch_all(client.as_ref())
+                .await
+                .unwrap();
+        assert_eq!(
+            series,
+            vec![
+                ("bar".to_string(), r#"{"online":true}"#.to_string()),
+                ("baz".to_string(), "{}".to_string()),
+                (
+                    "foo".to_string(),
+                    r#"{"address":"00:00","host":"rpi"}"#.to_string()
+                ),
+            ]
+        );
+
+        let points: Vec<(String, i64, String)> = sqlx::query_as(
+            "select name, timestamp, value from metric_points join metric_series on metric_series.id = series_id order by name, timestamp",
+        )
+        .fetch_all(client.as_ref())
+        .await
+        .unwrap();
+        assert_eq!(
+            points,
+            vec![
+                (
+                    "bar".to_string(),
+                    10,
+                    r#"{"type":"bool","value":true}"#.to_string()
+                ),
+                (
+                    "foo".to_string(),
+                    10,
+                    r#"{"type":"count","value":1}"#.to_string()
+                ),
+                // the latest value stored at the same time wins
+                (
+                    "foo".to_string(),
+                    20,
+                    r#"{"type":"count","value":3}"#.to_string()
+                ),
+            ]
+        );
+
+        let rollups: Vec<(String, String, i64)> = sqlx::query_as(
+            "select name, type, count from metric_rollups join metric_series on metric_series.id = series_id order by name",
+        )
+        .fetch_all(client.as_ref())
+        .await
+        .unwrap();
+        assert_eq!(
+            rollups,
+            vec![
+                ("baz".to_string(), "gauge".to_string(), 2),
+                ("foo".to_string(), "count".to_string(), 1),
+            ]
+        );
+
+        let tables: Vec<(String,)> = sqlx::query_as(
+            "select name from sqlite_master where type = 'table' and name in ('metrics', 'metrics_rollups')",
+        )
+        .fetch_all(client.as_ref())
+        .await
+        .unwrap();
+        assert!(tables.is_empty());
+    }
+}
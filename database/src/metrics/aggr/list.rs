@@ -1,49 +1,115 @@
+use std::borrow::Cow;
+
 use super::MetricAggr;
-use crate::metrics::MetricHeader;
+use crate::metrics::filter::MetricFilter;
+
+/// How the series are merged into the aggregated values.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GroupBy {
+    /// Every serie is aggregated on its own.
+    #[default]
+    Serie,
+    /// The series of a same name are merged when sharing the same values for those tags,
+    /// the other tags being dropped. Without any tag, all the series of a name are merged.
+    Tags(Vec<Cow<'static, str>>),
+}
 
 pub struct Command<'a> {
-    headers: &'a [MetricHeader],
+    filters: &'a [MetricFilter],
     timerange: (u64, u64),
     divisions: usize,
+    group_by: GroupBy,
+    rate: bool,
 }
 
 impl<'a> Command<'a> {
-    pub fn new(headers: &'a [MetricHeader], timerange: (u64, u64), divisions: usize) -> Self {
+    pub fn new(filters: &'a [MetricFilter], timerange: (u64, u64), divisions: usize) -> Self {
         Self {
-            headers,
+            filters,
             timerange,
             divisions,
+            group_by: GroupBy::Serie,
+            rate: false,
         }
     }
 
-    fn build_subset_headers_filter<'b>(
-        &self,
-        qb: &'b mut sqlx::QueryBuilder<'b, sqlx::Sqlite>,
-    ) -> &'b mut sqlx::QueryBuilder<'b, sqlx::Sqlite>
-    where
-        'a: 'b,
-    {
-        if self.headers.is_empty() {
-            qb
+    pub fn with_group_by(mut self, group_by: GroupBy) -> Self {
+        self.group_by = group_by;
+        self
+    }
+
+    /// Aggregates the count metrics as monotonic counters, providing their increase
+    /// and per second rate over each division.
+    pub fn with_rate(mut self, rate: bool) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// Pushes the increase of the counter since the previous row of the serie, the max
+    /// being the last value of a rollup. A lower value means the counter got reset
+    /// and started again from zero. A serie without any row before the window has
+    /// no increase for its first row.
+    fn push_increase(&self, qb: &mut sqlx::QueryBuilder<'_, sqlx::Sqlite>) {
+        if self.rate {
+            qb.push("case");
+            qb.push(" when type != 'count' or lag(max) over s is null then null");
+            qb.push(" when max >= lag(max) over s then max - lag(max) over s");
+            qb.push(" else max end");
         } else {
-            qb.push(" and (");
-            for (index, header) in self.headers.iter().enumerate() {
-                if index > 0 {
-                    qb.push(" or");
-                }
-                qb.push(" (name = ").push_bind(header.name.as_ref());
-                for (name, value) in header.tags.entries() {
-                    qb.push(" and json_extract(tags, ")
-                        .push_bind(format!("$.{name}"))
-                        .push(") = ");
-                    crate::tag_value_bind!(qb, value);
+            qb.push("null");
+        }
+    }
+
+    /// Pushes the tags the points are grouped with, the missing tags being removed
+    /// from the object by `json_patch`.
+    fn push_group_tags(&self, qb: &mut sqlx::QueryBuilder<'_, sqlx::Sqlite>) {
+        match self.group_by {
+            GroupBy::Serie => {
+                qb.push("tags");
+            }
+            GroupBy::Tags(ref names) if names.is_empty() => {
+                qb.push("'{}'");
+            }
+            GroupBy::Tags(ref names) => {
+                qb.push("json_patch('{}', json_object(");
+                for (index, name) in names.iter().enumerate() {
+                    if index > 0 {
+                        qb.push(", ");
+                    }
+                    qb.push_bind(name.to_string())
+                        .push(", tags -> ")
+                        .push_bind(format!("$.{name}"));
                 }
-                qb.push(")");
+                qb.push("))");
             }
-            qb.push(")")
         }
     }
 
+    /// Pushes the condition on the timestamps of the rows of `table`.
+    ///
+    /// With the rate, the last row of each serie before the window is selected too,
+    /// to compute the increase up to the first row in the window.
+    fn push_timerange(&self, qb: &mut sqlx::QueryBuilder<'_, sqlx::Sqlite>, table: &str) {
+        let (from_ts, to_ts) = self.timerange;
+        qb.push(format!(" where {table}.timestamp <= ")).push(to_ts);
+        if self.rate {
+            qb.push(format!(" and ({table}.timestamp > "))
+                .push(from_ts)
+                .push(format!(" or ({table}.series_id, {table}.timestamp) in ("))
+                .push(format!("select series_id, max(timestamp) from {table}"))
+                .push(" where timestamp <= ")
+                .push(from_ts)
+                .push(" group by series_id))");
+        } else {
+            qb.push(format!(" and {table}.timestamp > ")).push(from_ts);
+        }
+    }
+
+    /// Raw metrics and rollups are merged in a single subset, exposing for each row
+    /// its min, max, sum and count, so that both can be aggregated the same way.
+    ///
+    /// With the rate, the rows before the window are only removed once the increases
+    /// got computed.
     fn build_subset<'b>(
         &self,
         qb: &'b mut sqlx::QueryBuilder<'b, sqlx::Sqlite>,
@@ -52,6 +118,9 @@ impl<'a> Command<'a> {
         'a: 'b,
     {
         let (from_ts, to_ts) = self.timerange;
+        if self.rate {
+            qb.push("select * from (");
+        }
         qb.push("select");
         qb.push(" timestamp,");
         qb.push(" (timestamp - ")
@@ -64,13 +133,34 @@ impl<'a> Command<'a> {
             .push_bind(from_ts as i64)
             .push(")")
             .push(" as division,");
-        qb.push(" name,");
-        qb.push(" tags,");
-        qb.push(" value");
-        qb.push(" from metrics");
-        qb.push(" where timestamp > ").push(self.timerange.0);
-        qb.push(" and timestamp <= ").push(self.timerange.1);
-        self.build_subset_headers_filter(qb)
+        qb.push(" name, ");
+        self.push_group_tags(qb);
+        qb.push(" as group_tags, type, min, max, sum, count, ");
+        self.push_increase(qb);
+        qb.push(" as increase");
+        qb.push(" from (");
+        qb.push("select metric_points.timestamp, name, tags,");
+        qb.push(" json_extract(value, '$.type') as type,");
+        qb.push(" json_extract(value, '$.value') as min,");
+        qb.push(" json_extract(value, '$.value') as max,");
+        qb.push(" json_extract(value, '$.value') as sum,");
+        qb.push(" 1 as count");
+        qb.push(" from metric_points");
+        qb.push(" join metric_series on metric_series.id = metric_points.series_id");
+        self.push_timerange(qb, "metric_points");
+        qb.push(" union all");
+        qb.push(" select metric_rollups.timestamp, name, tags, type, min, max, sum, count");
+        qb.push(" from metric_rollups");
+        qb.push(" join metric_series on metric_series.id = metric_rollups.series_id");
+        self.push_timerange(qb, "metric_rollups");
+        qb.push(")");
+        qb.push(" where true");
+        crate::metrics::filter::push_filters(qb, self.filters);
+        if self.rate {
+            qb.push(" window s as (partition by name, tags order by timestamp)");
+            qb.push(") where timestamp > ").push(from_ts);
+        }
+        qb
     }
 
     fn build_count_subset<'b>(
@@ -81,15 +171,28 @@ impl<'a> Command<'a> {
         'a: 'b,
     {
         qb.push(" select");
-        qb.push(" json_object('from', min(timestamp), 'to', max(timestamp), 'count', count(timestamp)) as timestamp,");
-        qb.push(" name, tags,");
-        qb
-            .push(" json_object('type', 'count', 'min', min(json_extract(value, '$.value')), 'avg', avg(json_extract(value, '$.value')), 'max', max(json_extract(value, '$.value')), 'sum', sum(json_extract(value, '$.value'))) as value");
+        qb.push(" json_object('from', min(timestamp), 'to', max(timestamp), 'count', sum(count)) as timestamp,");
+        qb.push(" name, group_tags as tags,");
+        if self.rate {
+            let (from_ts, to_ts) = self.timerange;
+            let duration = (to_ts - from_ts) as f64 / self.divisions as f64;
+            qb.push(" json_object('type', 'rate', 'increase', total(increase), 'rate', total(increase) / ")
+                .push(duration)
+                .push(") as value");
+        } else {
+            qb.push(" json_object('type', 'count', 'min', min(min), 'avg', cast(sum(sum) as real) / sum(count), 'max', max(max), 'sum', sum(sum)) as value");
+        }
         qb.push(" from metrics_subset");
-        qb.push(" where json_extract(value, '$.type') = 'count'");
-        qb.push(" group by division, name, tags")
+        qb.push(" where type = 'count'");
+        qb.push(" group by division, name, group_tags")
     }
 
+    /// Each row of a group is considered as `count` points with its average value,
+    /// which is exact for the raw metrics and an approximation for the rollups.
+    ///
+    /// The percentiles use the nearest rank, by accumulating the number of points
+    /// of the rows sorted by value. As `sqrt` isn't available in sqlite, the standard deviation
+    /// is returned as a variance and fixed when reading the row.
     fn build_gauge_subset<'b>(
         &self,
         qb: &'b mut sqlx::QueryBuilder<'b, sqlx::Sqlite>,
@@ -98,13 +201,37 @@ impl<'a> Command<'a> {
         'a: 'b,
     {
         qb.push(" select");
-        qb.push(" json_object('from', min(timestamp), 'to', max(timestamp), 'count', count(timestamp)) as timestamp,");
-        qb.push(" name, tags,");
-        qb
-            .push(" json_object('type', 'gauge', 'min', min(json_extract(value, '$.value')), 'avg', avg(json_extract(value, '$.value')), 'max', max(json_extract(value, '$.value'))) as value");
+        qb.push(" json_object('from', min(timestamp), 'to', max(timestamp), 'count', sum(count)) as timestamp,");
+        qb.push(" name, group_tags as tags,");
+        qb.push(" json_object('type', 'gauge',");
+        qb.push(" 'min', min(min),");
+        qb.push(" 'avg', cast(sum(sum) as real) / sum(count),");
+        qb.push(" 'max', max(max),");
+        qb.push(" 'first', min(first),");
+        qb.push(" 'last', min(last),");
+        for (key, ratio) in [("median", 0.5), ("p90", 0.9), ("p95", 0.95), ("p99", 0.99)] {
+            qb.push(" '")
+                .push(key)
+                .push("', min(case when cumulative >= ")
+                .push(ratio)
+                .push(" * total then mean end),");
+        }
+        qb.push(" 'stddev', max(sum(count * mean * mean) / sum(count) - (cast(sum(sum) as real) / sum(count)) * (cast(sum(sum) as real) / sum(count)), 0)");
+        qb.push(") as value");
+        qb.push(" from (");
+        qb.push("select *,");
+        qb.push(" cast(sum as real) / count as mean,");
+        qb.push(" first_value(cast(sum as real) / count) over (w order by timestamp) as first,");
+        qb.push(
+            " first_value(cast(sum as real) / count) over (w order by timestamp desc) as last,",
+        );
+        qb.push(" sum(count) over (w order by cast(sum as real) / count rows unbounded preceding) as cumulative,");
+        qb.push(" sum(count) over w as total");
         qb.push(" from metrics_subset");
-        qb.push(" where json_extract(value, '$.type') = 'gauge'");
-        qb.push(" group by division, name, tags")
+        qb.push(" where type = 'gauge'");
+        qb.push(" window w as (partition by division, name, group_tags)");
+        qb.push(")");
+        qb.push(" group by division, name, group_tags")
     }
 
     pub async fn execute<'c, E: sqlx::Executor<'c, Database = sqlx::Sqlite>>(
@@ -137,6 +264,7 @@ impl<'a> Command<'a> {
 mod tests {
     use crate::helper::now;
     use crate::metrics::entity::MetricValue;
+    use crate::metrics::filter::MetricFilter;
     use crate::metrics::MetricHeader;
 
     const NOW: u64 = 633009600;
@@ -176,11 +304,11 @@ mod tests {
     async fn should_build_query() {
         let current = now();
         let before = current - 60 * 60; // 1h gap
-        let headers = vec![MetricHeader::new("hello.world").with_tag("host", "whatever")];
+        let filters = vec![MetricFilter::new("hello.world").with_tag("host", "whatever")];
 
         let db = crate::Client::test().await;
 
-        let list = super::Command::new(&headers, (before, current), 10)
+        let list = super::Command::new(&filters, (before, current), 10)
             .execute(db.as_ref())
             .await
             .unwrap();
@@ -202,7 +330,7 @@ mod tests {
         .await;
         assert_eq!(generated.len(), 217);
 
-        let list = super::Command::new(&[header], (ONE_WEEK_AGO + 1, NOW + 1), 7)
+        let list = super::Command::new(&[header.into()], (ONE_WEEK_AGO + 1, NOW + 1), 7)
             .execute(db.as_ref())
             .await
             .unwrap();
@@ -227,7 +355,7 @@ mod tests {
         .await;
         assert_eq!(generated.len(), 73);
 
-        let list = super::Command::new(&[header], (ONE_WEEK_AGO, NOW), 7)
+        let list = super::Command::new(&[header.into()], (ONE_WEEK_AGO, NOW), 7)
             .execute(db.as_ref())
             .await
             .unwrap();
@@ -236,4 +364,262 @@ mod tests {
         assert_eq!(list[1].timerange.count, 24);
         assert_eq!(list[2].timerange.count, 1);
     }
+
+    #[tokio::test]
+    async fn should_aggregate_rollups_with_raw_values() {
+        let db = crate::Client::test().await;
+
+        let header = MetricHeader::new("foo").with_tag("host", "rpi");
+        crate::helper::create_metrics(
+            &db,
+            header.clone(),
+            (10..30).map(|index| (index * 10, MetricValue::count(index))),
+        )
+        .await;
+
+        let before = super::Command::new(&[MetricFilter::from(&header)], (50, 300), 1)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+
+        crate::metrics::rollup::create::Command::new(200, 50)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+
+        let after = super::Command::new(&[header.into()], (50, 300), 1)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+
+        assert_eq!(before.len(), 1);
+        assert_eq!(after.len(), 1);
+        assert_eq!(before[0].timerange.count, 20);
+        assert_eq!(after[0].timerange.count, 20);
+        let before = before[0].value.as_count().unwrap();
+        let after = after[0].value.as_count().unwrap();
+        assert_eq!(after.min, before.min);
+        assert_eq!(after.max, before.max);
+        assert_eq!(after.sum, before.sum);
+        assert_eq!(after.avg, before.avg);
+        assert_eq!(after.avg, 19.5);
+    }
+
+    #[tokio::test]
+    async fn should_group_series_by_tags() {
+        let db = crate::Client::test().await;
+
+        for (address, room, value) in [
+            ("00:01", "upstairs", 20.0),
+            ("00:02", "upstairs", 22.0),
+            ("00:03", "downstairs", 18.0),
+        ] {
+            let header = MetricHeader::new("temperature")
+                .with_tag("address", address)
+                .with_tag("room", room);
+            crate::helper::create_metrics(
+                &db,
+                header,
+                (1..=10).map(|index| (index * 10, MetricValue::gauge(value))),
+            )
+            .await;
+        }
+        // without the grouping tag
+        crate::helper::create_metrics(
+            &db,
+            MetricHeader::new("temperature").with_tag("address", "00:04"),
+            (1..=10).map(|index| (index * 10, MetricValue::gauge(30.0))),
+        )
+        .await;
+
+        let filters = [MetricFilter::new("temperature")];
+
+        let list = super::Command::new(&filters, (0, 101), 1)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 4);
+
+        let mut list = super::Command::new(&filters, (0, 101), 1)
+            .with_group_by(super::GroupBy::Tags(vec!["room".into()]))
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        list.sort_by(|first, second| {
+            first
+                .value
+                .as_gauge()
+                .unwrap()
+                .avg
+                .total_cmp(&second.value.as_gauge().unwrap().avg)
+        });
+        assert_eq!(list.len(), 3);
+        assert_eq!(
+            list[0].header,
+            MetricHeader::new("temperature").with_tag("room", "downstairs")
+        );
+        assert_eq!(list[0].timerange.count, 10);
+        assert_eq!(
+            list[1].header,
+            MetricHeader::new("temperature").with_tag("room", "upstairs")
+        );
+        assert_eq!(list[1].timerange.count, 20);
+        let upstairs = list[1].value.as_gauge().unwrap();
+        assert_eq!(upstairs.min, 20.0);
+        assert_eq!(upstairs.avg, 21.0);
+        assert_eq!(upstairs.max, 22.0);
+        assert_eq!(list[2].header, MetricHeader::new("temperature"));
+        assert_eq!(list[2].value.as_gauge().unwrap().avg, 30.0);
+
+        let list = super::Command::new(&filters, (0, 101), 1)
+            .with_group_by(super::GroupBy::Tags(Vec::new()))
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].header, MetricHeader::new("temperature"));
+        assert_eq!(list[0].timerange.count, 40);
+        assert_eq!(list[0].value.as_gauge().unwrap().avg, 22.5);
+    }
+
+    #[tokio::test]
+    async fn should_sum_counts_across_series() {
+        let db = crate::Client::test().await;
+
+        for (host, value) in [("rpi", 2), ("nas", 3)] {
+            crate::helper::create_metrics(
+                &db,
+                MetricHeader::new("requests").with_tag("host", host),
+                (1..=10).map(|index| (index * 10, MetricValue::count(value))),
+            )
+            .await;
+        }
+
+        let list = super::Command::new(&[MetricFilter::new("requests")], (0, 101), 2)
+            .with_group_by(super::GroupBy::Tags(Vec::new()))
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 2);
+        for item in list {
+            assert!(item.header.tags.is_empty());
+            let value = item.value.as_count().unwrap();
+            assert_eq!(value.sum, 25);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_compute_gauge_statistics() {
+        let db = crate::Client::test().await;
+
+        // the values from 1 to 100, shuffled
+        let header = MetricHeader::new("foo");
+        crate::helper::create_metrics(
+            &db,
+            header.clone(),
+            (0..100).map(|index| (index + 1, MetricValue::gauge((index * 37 % 100 + 1) as f64))),
+        )
+        .await;
+
+        let list = super::Command::new(&[header.into()], (0, 101), 1)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        let value = list[0].value.as_gauge().unwrap();
+        assert_eq!(value.min, 1.0);
+        assert_eq!(value.avg, 50.5);
+        assert_eq!(value.max, 100.0);
+        assert_eq!(value.first, 1.0);
+        assert_eq!(value.last, 64.0);
+        assert_eq!(value.median, 50.0);
+        assert_eq!(value.p90, 90.0);
+        assert_eq!(value.p95, 95.0);
+        assert_eq!(value.p99, 99.0);
+        assert!((value.stddev - 28.866).abs() < 0.001);
+    }
+
+    #[tokio::test]
+    async fn should_compute_counter_rates() {
+        let db = crate::Client::test().await;
+
+        // reset after the 4th value
+        let header = MetricHeader::new("restarts").with_tag("host", "rpi");
+        crate::helper::create_metrics(
+            &db,
+            header.clone(),
+            [0, 10, 20, 30, 5, 15]
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| (index as u64 * 10 + 5, MetricValue::count(value))),
+        )
+        .await;
+        crate::helper::create_metrics(
+            &db,
+            MetricHeader::new("restarts").with_tag("host", "nas"),
+            (1..=6).map(|index| (index * 10 - 5, MetricValue::count(index * 3))),
+        )
+        .await;
+
+        let list = super::Command::new(&[header.into()], (0, 60), 1)
+            .with_rate(true)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        let value = list[0].value.as_rate().unwrap();
+        assert_eq!(value.increase, 45.0);
+        assert_eq!(value.rate, 0.75);
+
+        // the increases are computed for each serie before being merged
+        let list = super::Command::new(&[MetricFilter::new("restarts")], (0, 60), 1)
+            .with_group_by(super::GroupBy::Tags(Vec::new()))
+            .with_rate(true)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        let value = list[0].value.as_rate().unwrap();
+        assert_eq!(value.increase, 60.0);
+        assert_eq!(value.rate, 1.0);
+    }
+
+    #[tokio::test]
+    async fn should_compute_counter_rates_across_window_start() {
+        let db = crate::Client::test().await;
+
+        let header = MetricHeader::new("bytes");
+        crate::helper::create_metrics(
+            &db,
+            header.clone(),
+            [(0, 100), (50, 200), (110, 300), (150, 400)]
+                .into_iter()
+                .map(|(ts, value)| (ts, MetricValue::count(value))),
+        )
+        .await;
+
+        let filters = [MetricFilter::from(header)];
+        let list = super::Command::new(&filters, (100, 160), 1)
+            .with_rate(true)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].timerange.count, 2);
+        assert_eq!(list[0].value.as_rate().unwrap().increase, 200.0);
+
+        // the previous point is now in a rollup
+        crate::metrics::rollup::create::Command::new(100, 100)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        let list = super::Command::new(&filters, (100, 160), 1)
+            .with_rate(true)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].timerange.count, 2);
+        assert_eq!(list[0].value.as_rate().unwrap().increase, 200.0);
+    }
 }
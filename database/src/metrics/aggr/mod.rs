@@ -25,11 +25,83 @@ pub struct MetricCountAggr {
     pub sum: u64,
 }
 
+/// The statistics other than `min`, `avg` and `max` consider a rolled up bucket
+/// as many points of its average value, and are approximated for the old metrics.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct MetricGaugeAggr {
     pub min: f64,
     pub avg: f64,
     pub max: f64,
+    pub first: f64,
+    pub last: f64,
+    pub median: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+    /// Population standard deviation.
+    pub stddev: f64,
+}
+
+impl MetricGaugeAggr {
+    pub fn get(&self, statistic: Statistic) -> f64 {
+        match statistic {
+            Statistic::Min => self.min,
+            Statistic::Avg => self.avg,
+            Statistic::Max => self.max,
+            Statistic::First => self.first,
+            Statistic::Last => self.last,
+            Statistic::Median => self.median,
+            Statistic::P90 => self.p90,
+            Statistic::P95 => self.p95,
+            Statistic::P99 => self.p99,
+            Statistic::Stddev => self.stddev,
+        }
+    }
+}
+
+/// Value picked from an aggregated gauge.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Statistic {
+    Min,
+    #[default]
+    Avg,
+    Max,
+    First,
+    Last,
+    Median,
+    P90,
+    P95,
+    P99,
+    Stddev,
+}
+
+impl Statistic {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Min => "min",
+            Self::Avg => "avg",
+            Self::Max => "max",
+            Self::First => "first",
+            Self::Last => "last",
+            Self::Median => "median",
+            Self::P90 => "p90",
+            Self::P95 => "p95",
+            Self::P99 => "p99",
+            Self::Stddev => "stddev",
+        }
+    }
+}
+
+/// Count metrics considered as monotonic counters.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct MetricRateAggr {
+    /// Increase of the counter over the period, the resets being ignored.
+    pub increase: f64,
+    /// Average increase per second.
+    pub rate: f64,
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -37,6 +109,7 @@ pub struct MetricGaugeAggr {
 pub enum MetricValueAggr {
     Count(MetricCountAggr),
     Gauge(MetricGaugeAggr),
+    Rate(MetricRateAggr),
 }
 
 impl MetricValueAggr {
@@ -67,6 +140,20 @@ impl MetricValueAggr {
             _ => None,
         }
     }
+
+    pub fn as_rate(&self) -> Option<&MetricRateAggr> {
+        match self {
+            Self::Rate(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    pub fn into_rate(self) -> Option<MetricRateAggr> {
+        match self {
+            Self::Rate(inner) => Some(inner),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -84,7 +171,11 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for MetricAggr {
         let Json(timerange): Json<TimeRange> = row.try_get(0)?;
         let metric_name: String = row.try_get(1)?;
         let Json(metric_tags): Json<MetricTags> = row.try_get(2)?;
-        let Json(metric_value): Json<MetricValueAggr> = row.try_get(3)?;
+        let Json(mut metric_value): Json<MetricValueAggr> = row.try_get(3)?;
+        // the query provides the variance
+        if let MetricValueAggr::Gauge(ref mut inner) = metric_value {
+            inner.stddev = inner.stddev.sqrt();
+        }
 
         Ok(Self {
             timerange,
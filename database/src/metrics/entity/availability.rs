@@ -0,0 +1,168 @@
+use sqlx::types::Json;
+
+use crate::metrics::filter::MetricFilter;
+use crate::metrics::{MetricHeader, MetricTags};
+
+/// Share of the time a boolean serie spent at `true`.
+#[derive(Clone, Debug)]
+pub struct Availability {
+    pub header: MetricHeader,
+    /// Between 0 and 1.
+    pub ratio: f64,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Availability {
+    fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        use sqlx::Row;
+
+        let metric_name: String = row.try_get(0)?;
+        let Json(metric_tags): Json<MetricTags> = row.try_get(1)?;
+
+        Ok(Self {
+            header: MetricHeader {
+                name: metric_name.into(),
+                tags: metric_tags,
+            },
+            ratio: row.try_get(2)?,
+        })
+    }
+}
+
+/// Computes, for each boolean serie, the ratio of the window during which it was `true`.
+///
+/// Unchanged values are not sent again by the agents, so each point is considered
+/// valid until the next one, or until the end of the window for the last one.
+/// The point preceding the window gives the state at its beginning.
+pub struct Command<'a> {
+    filters: &'a [MetricFilter],
+    window: (u64, u64),
+}
+
+impl<'a> Command<'a> {
+    pub fn new(filters: &'a [MetricFilter], window: (u64, u64)) -> Self {
+        Self { filters, window }
+    }
+
+    pub async fn execute<'c, E: sqlx::Executor<'c, Database = sqlx::Sqlite>>(
+        self,
+        executor: E,
+    ) -> sqlx::Result<Vec<Availability>> {
+        let (from_ts, to_ts) = (self.window.0 as i64, self.window.1 as i64);
+        // series_subset
+        let mut qb = sqlx::QueryBuilder::new("with series_subset as (");
+        qb.push("select id, name, tags from metric_series");
+        qb.push(" where true");
+        crate::metrics::filter::push_filters(&mut qb, self.filters);
+        qb.push(")");
+        // points with the timestamp of the next one
+        qb.push(", points as (");
+        qb.push("select series_id, timestamp,");
+        qb.push(" json_extract(value, '$.value') as state,");
+        qb.push(
+            " lead(timestamp) over (partition by series_id order by timestamp) as next_timestamp",
+        );
+        qb.push(" from metric_points");
+        qb.push(" where series_id in (select id from series_subset)");
+        qb.push(" and json_extract(value, '$.type') = 'bool'");
+        qb.push(" and timestamp <= ").push_bind(to_ts);
+        qb.push(")");
+        // periods of each state, clamped to the window
+        qb.push(", periods as (");
+        qb.push("select series_id, state,");
+        qb.push(" max(timestamp, ")
+            .push_bind(from_ts)
+            .push(") as since,");
+        qb.push(" coalesce(next_timestamp, ")
+            .push_bind(to_ts)
+            .push(") as until");
+        qb.push(" from points");
+        qb.push(" where coalesce(next_timestamp, ")
+            .push_bind(to_ts)
+            .push(") > ")
+            .push_bind(from_ts);
+        qb.push(")");
+        qb.push(" select series_subset.name, series_subset.tags,");
+        qb.push(" cast(sum(state * (until - since)) as real) / sum(until - since) as ratio");
+        qb.push(" from periods");
+        qb.push(" join series_subset on series_subset.id = periods.series_id");
+        qb.push(" group by periods.series_id");
+        qb.push(" having sum(until - since) > 0");
+        //
+        let query = qb.build_query_as::<'_, Availability>();
+        let rows = query.fetch_all(executor).await?;
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::entity::MetricValue;
+    use crate::metrics::filter::MetricFilter;
+    use crate::metrics::MetricHeader;
+
+    #[tokio::test]
+    async fn should_compute_ratio_in_window() {
+        let db = crate::Client::test().await;
+
+        let header = MetricHeader::new("up").with_tag("target", "nas");
+        crate::helper::create_metrics(
+            &db,
+            header.clone(),
+            [
+                // before the window, up at its beginning
+                (0, MetricValue::bool(true)),
+                (40, MetricValue::bool(false)),
+                (50, MetricValue::bool(true)),
+                (60, MetricValue::bool(true)),
+                // after the window
+                (200, MetricValue::bool(false)),
+            ]
+            .into_iter(),
+        )
+        .await;
+
+        let other = MetricHeader::new("up").with_tag("target", "router");
+        crate::helper::create_metrics(
+            &db,
+            other.clone(),
+            [(150, MetricValue::bool(false))].into_iter(),
+        )
+        .await;
+
+        let found = super::Command::new(&[MetricFilter::from(&header)], (10, 110))
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].header, header);
+        assert!((found[0].ratio - 0.9).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn should_ignore_series_without_point() {
+        let db = crate::Client::test().await;
+
+        let header = MetricHeader::new("up").with_tag("target", "nas");
+        crate::helper::create_metrics(
+            &db,
+            header.clone(),
+            [(150, MetricValue::bool(true))].into_iter(),
+        )
+        .await;
+        let count = MetricHeader::new("count").with_tag("target", "nas");
+        crate::helper::create_metrics(
+            &db,
+            count.clone(),
+            [(20, MetricValue::count(1))].into_iter(),
+        )
+        .await;
+
+        let found = super::Command::new(&[MetricFilter::new("up"), count.into()], (10, 110))
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+
+        assert!(found.is_empty());
+    }
+}
@@ -1,5 +1,11 @@
+use std::collections::HashSet;
+
 use super::Metric;
 
+/// Maximum number of metrics in a single batch, keeps the generated insert query
+/// under sqlite's limit of bound parameters.
+pub const MAX_BATCH_SIZE: usize = 1000;
+
 pub struct Command<'a>(&'a [Metric]);
 
 impl<'a> Command<'a> {
@@ -8,20 +14,147 @@ impl<'a> Command<'a> {
         Self(list)
     }
 
-    pub async fn execute<'c, E: sqlx::Executor<'c, Database = sqlx::Sqlite>>(
+    /// Creates the missing series and stores the points.
+    ///
+    /// A point stored twice for the same serie and timestamp replaces the previous value.
+    /// Runs in its own transaction, nested in the caller's one when given a transaction.
+    pub async fn execute<'c, A: sqlx::Acquire<'c, Database = sqlx::Sqlite>>(
         self,
-        executor: E,
+        executor: A,
     ) -> sqlx::Result<u64> {
-        let mut query_builder: sqlx::QueryBuilder<sqlx::Sqlite> =
-            sqlx::QueryBuilder::new("insert into metrics (timestamp, name, tags, value)");
-        query_builder.push_values(self.0.iter(), |mut b, entry| {
-            b.push_bind(entry.timestamp as i64)
-                .push_bind(entry.header.name.as_ref())
-                .push_bind(sqlx::types::Json(&entry.header.tags))
+        if self.0.is_empty() {
+            return Ok(0);
+        }
+        let entries = self
+            .0
+            .iter()
+            .map(|entry| {
+                entry
+                    .header
+                    .tags
+                    .to_canonical_json()
+                    .map(|tags| (entry, tags))
+            })
+            .collect::<serde_json::Result<Vec<_>>>()
+            .map_err(|err| sqlx::Error::Encode(Box::new(err)))?;
+        let series = entries
+            .iter()
+            .map(|(entry, tags)| (entry.header.name.as_ref(), tags.as_str()))
+            .collect::<HashSet<_>>();
+
+        let mut tx = executor.begin().await?;
+
+        let mut qb: sqlx::QueryBuilder<sqlx::Sqlite> =
+            sqlx::QueryBuilder::new("insert into metric_series (name, tags)");
+        qb.push_values(series.iter(), |mut b, (name, tags)| {
+            b.push_bind(*name).push_bind(*tags);
+        });
+        qb.push(" on conflict (name, tags) do nothing");
+        qb.build().execute(&mut *tx).await?;
+
+        let mut qb: sqlx::QueryBuilder<sqlx::Sqlite> =
+            sqlx::QueryBuilder::new("with batch (name, tags, timestamp, value) as (");
+        qb.push_values(entries.iter(), |mut b, (entry, tags)| {
+            b.push_bind(entry.header.name.as_ref())
+                .push_bind(tags.as_str())
+                .push_bind(entry.timestamp as i64)
                 .push_bind(sqlx::types::Json(&entry.value));
         });
-        let query = query_builder.build();
-        let res = query.execute(executor).await?;
+        qb.push(")");
+        qb.push(" insert into metric_points (series_id, timestamp, value)");
+        qb.push(" select metric_series.id, batch.timestamp, batch.value");
+        qb.push(" from batch");
+        qb.push(" join metric_series on metric_series.name = batch.name and metric_series.tags = batch.tags");
+        // the where clause is required by sqlite to parse the upsert
+        qb.push(" where true");
+        qb.push(" on conflict (series_id, timestamp) do update set value = excluded.value");
+        let res = qb.build().execute(&mut *tx).await?;
+
+        tx.commit().await?;
         Ok(res.rows_affected())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::entity::{Metric, MetricValue};
+    use crate::metrics::MetricHeader;
+
+    #[tokio::test]
+    async fn should_reuse_series_regardless_of_tags_order() {
+        let db = crate::Client::test().await;
+
+        let metrics = vec![
+            Metric {
+                timestamp: 0,
+                header: MetricHeader::new("foo")
+                    .with_tag("host", "rpi")
+                    .with_tag("address", "00:00"),
+                value: MetricValue::count(1),
+            },
+            Metric {
+                timestamp: 1,
+                header: MetricHeader::new("foo")
+                    .with_tag("address", "00:00")
+                    .with_tag("host", "rpi"),
+                value: MetricValue::count(2),
+            },
+            Metric {
+                timestamp: 1,
+                header: MetricHeader::new("foo")
+                    .with_tag("address", "00:00")
+                    .with_tag("host", "rpi"),
+                value: MetricValue::count(3),
+            },
+        ];
+        super::Command::new(&metrics)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+
+        let series: Vec<(String, String)> = sqlx::query_as("select name, tags from metric_series")
+            .fetch_all(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(
+            series,
+            vec![("foo".into(), r#"{"address":"00:00","host":"rpi"}"#.into())]
+        );
+        let values: Vec<(i64, String)> =
+            sqlx::query_as("select timestamp, value from metric_points order by timestamp")
+                .fetch_all(db.as_ref())
+                .await
+                .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                (0, r#"{"type":"count","value":1}"#.into()),
+                (1, r#"{"type":"count","value":3}"#.into())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_run_in_caller_transaction() {
+        let db = crate::Client::test().await;
+        let metrics = vec![Metric {
+            timestamp: 0,
+            header: MetricHeader::new("foo"),
+            value: MetricValue::count(1),
+        }];
+
+        let mut tx = db.as_ref().begin().await.unwrap();
+        let count = super::Command::new(&metrics)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        tx.rollback().await.unwrap();
+
+        let (count,): (i64,) = sqlx::query_as("select count(*) from metric_points")
+            .fetch_one(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+}
@@ -0,0 +1,40 @@
+/// Removes the points older than `before`.
+pub struct Command {
+    before: u64,
+    types: Option<&'static [&'static str]>,
+}
+
+impl Command {
+    #[inline]
+    pub fn new(before: u64) -> Self {
+        Self {
+            before,
+            types: None,
+        }
+    }
+
+    /// Only removes the points with one of those value types.
+    #[inline]
+    pub fn with_types(mut self, types: &'static [&'static str]) -> Self {
+        self.types = Some(types);
+        self
+    }
+
+    pub async fn execute<'c, E: sqlx::Executor<'c, Database = sqlx::Sqlite>>(
+        self,
+        executor: E,
+    ) -> sqlx::Result<u64> {
+        let mut qb = sqlx::QueryBuilder::new("delete from metric_points where timestamp < ");
+        qb.push_bind(self.before as i64);
+        if let Some(types) = self.types {
+            qb.push(" and json_extract(value, '$.type') in (");
+            let mut separated = qb.separated(", ");
+            for kind in types {
+                separated.push_bind(*kind);
+            }
+            separated.push_unseparated(")");
+        }
+        let res = qb.build().execute(executor).await?;
+        Ok(res.rows_affected())
+    }
+}
@@ -1,17 +1,17 @@
 use crate::metrics::entity::Metric;
-use crate::metrics::MetricHeader;
+use crate::metrics::filter::MetricFilter;
 
-/// Right now, we expect tags to match exactly.
+/// Finds the latest point in the window of every serie selected by the filters.
 pub struct Command<'a> {
-    headers: &'a [MetricHeader],
+    filters: &'a [MetricFilter],
     window: (u64, u64),
     limit: Option<usize>,
 }
 
 impl<'a> Command<'a> {
-    pub fn new(headers: &'a [MetricHeader], window: (u64, u64), limit: Option<usize>) -> Self {
+    pub fn new(filters: &'a [MetricFilter], window: (u64, u64), limit: Option<usize>) -> Self {
         Self {
-            headers,
+            filters,
             window,
             limit,
         }
@@ -21,40 +21,23 @@ impl<'a> Command<'a> {
         self,
         executor: E,
     ) -> sqlx::Result<Vec<Metric>> {
-        // metrics_subset
-        let mut qb = sqlx::QueryBuilder::new("with metrics_subset as (");
-        qb.push("select timestamp, name, tags, value,");
-        qb.push(" row_number() over (partition by name, tags order by timestamp desc) as idx");
-        qb.push(" from metrics");
-
-        qb.push(" where timestamp >= ")
+        // series_subset
+        let mut qb = sqlx::QueryBuilder::new("with series_subset as (");
+        qb.push("select id, name, tags from metric_series");
+        qb.push(" where true");
+        crate::metrics::filter::push_filters(&mut qb, self.filters);
+        qb.push(")");
+        qb.push(" select metric_points.timestamp, series_subset.name, series_subset.tags, metric_points.value");
+        qb.push(" from series_subset");
+        qb.push(" join metric_points on metric_points.series_id = series_subset.id");
+        qb.push(" and metric_points.timestamp = (");
+        qb.push("select max(timestamp) from metric_points");
+        qb.push(" where series_id = series_subset.id");
+        qb.push(" and timestamp >= ")
             .push_bind(self.window.0 as i64)
             .push(" and timestamp <= ")
             .push_bind(self.window.1 as i64);
         qb.push(")");
-        qb.push(" select timestamp, name, tags, value");
-        qb.push(" from metrics_subset");
-        qb.push(" where idx = 1");
-        if !self.headers.is_empty() {
-            qb.push(" and (");
-            for (index, header) in self.headers.iter().enumerate() {
-                if index > 0 {
-                    qb.push(" or");
-                }
-                qb.push(" (")
-                    .push("name = ")
-                    .push_bind(header.name.as_ref());
-                for (name, value) in header.tags.entries() {
-                    qb.push(" and")
-                        .push(" json_extract(tags, ")
-                        .push_bind(format!("$.{name}"))
-                        .push(") = ");
-                    crate::tag_value_bind!(qb, value);
-                }
-                qb.push(")");
-            }
-            qb.push(")");
-        }
         qb.push(" order by timestamp desc");
         if let Some(limit) = self.limit {
             qb.push(" limit ").push_bind(limit as i64);
@@ -71,6 +54,7 @@ mod tests {
     use std::collections::HashSet;
 
     use crate::metrics::entity::{Metric, MetricValue};
+    use crate::metrics::filter::MetricFilter;
     use crate::metrics::MetricHeader;
 
     async fn create_metrics(
@@ -115,7 +99,7 @@ mod tests {
         .await;
         assert_eq!(expected_events.len(), 10);
 
-        let found = super::Command::new(&[expected_header], (0, 20), Some(10))
+        let found = super::Command::new(&[expected_header.into()], (0, 20), Some(10))
             .execute(db.as_ref())
             .await
             .unwrap();
@@ -149,7 +133,7 @@ mod tests {
         )
         .await;
 
-        let found = super::Command::new(&[expected_header], (0, 100), Some(10))
+        let found = super::Command::new(&[expected_header.into()], (0, 100), Some(10))
             .execute(db.as_ref())
             .await
             .unwrap();
@@ -195,10 +179,14 @@ mod tests {
         )
         .await;
 
-        let found = super::Command::new(&[first_header, second_header], (0, 100), Some(10))
-            .execute(db.as_ref())
-            .await
-            .unwrap();
+        let found = super::Command::new(
+            &[first_header.into(), second_header.into()],
+            (0, 100),
+            Some(10),
+        )
+        .execute(db.as_ref())
+        .await
+        .unwrap();
 
         assert_eq!(found.len(), 2);
         let found: HashSet<_> = found.into_iter().map(|item| item.timestamp).collect();
@@ -206,6 +194,45 @@ mod tests {
         assert!(found.contains(&29));
     }
 
+    #[tokio::test]
+    async fn should_find_latest_for_any_tag_value() {
+        let db = crate::Client::test().await;
+
+        for (index, address) in ["00:00:00:00:01", "00:00:00:00:02"].into_iter().enumerate() {
+            let header = MetricHeader::new("power")
+                .with_tag("address", address)
+                .with_tag("name", format!("device-{index}"));
+            create_metrics(
+                &db,
+                header,
+                (0..5).map(|ts| (ts + index as u64, MetricValue::gauge(ts as f64))),
+            )
+            .await;
+        }
+        let _other = create_metrics(
+            &db,
+            MetricHeader::new("battery").with_tag("address", "00:00:00:00:01"),
+            (0..5).map(|ts| (ts, MetricValue::gauge(100.0))),
+        )
+        .await;
+
+        let found = super::Command::new(&[MetricFilter::new("power")], (0, 100), None)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+
+        assert_eq!(found.len(), 2);
+        let found: HashSet<_> = found
+            .iter()
+            .map(|item| {
+                let address = item.header.tags.get("address").unwrap().as_text().unwrap();
+                (address.to_string(), item.timestamp)
+            })
+            .collect();
+        assert!(found.contains(&(String::from("00:00:00:00:01"), 4)));
+        assert!(found.contains(&(String::from("00:00:00:00:02"), 5)));
+    }
+
     #[tokio::test]
     async fn should_return_events_in_window() {
         let db = crate::Client::test().await;
@@ -219,7 +246,7 @@ mod tests {
         .await;
         assert_eq!(events.len(), 100);
 
-        let found = super::Command::new(&[header], (15, 20), Some(10))
+        let found = super::Command::new(&[header.into()], (15, 20), Some(10))
             .execute(db.as_ref())
             .await
             .unwrap();
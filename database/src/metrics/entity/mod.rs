@@ -1,6 +1,8 @@
 use sqlx::types::Json;
 
+pub mod availability;
 pub mod create;
+pub mod delete;
 pub mod find_latest;
 pub mod helper;
 
@@ -0,0 +1,351 @@
+use std::borrow::Cow;
+
+use crate::metrics::{MetricHeader, MetricName, MetricTagValue};
+
+/// How the name of a serie is matched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NameFilter {
+    Equal(MetricName),
+    Prefix(Cow<'static, str>),
+}
+
+/// How the value of a tag is matched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TagFilter {
+    /// The tag is defined, whatever its value.
+    Any,
+    Equal(MetricTagValue),
+    /// The tag has another value, or is not defined.
+    NotEqual(MetricTagValue),
+    /// The tag has one of the values, an empty list matching nothing.
+    In(Vec<MetricTagValue>),
+    /// The tag is a text starting with the given value.
+    Prefix(Cow<'static, str>),
+    /// The tag is a text matching the given pattern, supporting `*`, `?` and `[...]`.
+    Glob(Cow<'static, str>),
+}
+
+impl<V: Into<MetricTagValue>> From<V> for TagFilter {
+    fn from(value: V) -> Self {
+        Self::Equal(value.into())
+    }
+}
+
+/// Selects the series to query.
+///
+/// The tags that are not listed match any value, so a filter with only a name
+/// selects every serie having this name.
+///
+/// ```rust
+/// use chezmoi_database::metrics::filter::{MetricFilter, TagFilter};
+///
+/// let filter = MetricFilter::name_prefix("atc-thermometer.")
+///     .with_tag("address", TagFilter::Prefix("A4:C1:38".into()));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetricFilter {
+    pub name: NameFilter,
+    /// All the conditions have to be fulfilled.
+    pub tags: Vec<(Cow<'static, str>, TagFilter)>,
+}
+
+impl From<MetricHeader> for MetricFilter {
+    fn from(value: MetricHeader) -> Self {
+        Self {
+            name: NameFilter::Equal(value.name),
+            tags: value
+                .tags
+                .0
+                .into_iter()
+                .map(|(name, value)| (name, TagFilter::Equal(value)))
+                .collect(),
+        }
+    }
+}
+
+impl From<&MetricHeader> for MetricFilter {
+    fn from(value: &MetricHeader) -> Self {
+        Self::from(value.clone())
+    }
+}
+
+impl MetricFilter {
+    pub fn new(name: impl Into<MetricName>) -> Self {
+        Self {
+            name: NameFilter::Equal(name.into()),
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn name_prefix(prefix: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: NameFilter::Prefix(prefix.into()),
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn with_tag<N: Into<Cow<'static, str>>, F: Into<TagFilter>>(
+        mut self,
+        name: N,
+        filter: F,
+    ) -> Self {
+        self.tags.push((name.into(), filter.into()));
+        self
+    }
+
+    /// Pushes the condition on the `name` and `tags` columns of the series.
+    fn push_condition<'a>(&'a self, qb: &mut sqlx::QueryBuilder<'a, sqlx::Sqlite>) {
+        match self.name {
+            NameFilter::Equal(ref name) => qb.push("name = ").push_bind(name.as_ref()),
+            NameFilter::Prefix(ref prefix) => qb
+                .push("instr(name, ")
+                .push_bind(prefix.as_ref())
+                .push(") = 1"),
+        };
+        for (name, filter) in self.tags.iter() {
+            let path = format!("$.{name}");
+            qb.push(" and ");
+            match filter {
+                TagFilter::Any => {
+                    qb.push("json_type(tags, ")
+                        .push_bind(path)
+                        .push(") is not null");
+                }
+                TagFilter::Equal(value) => {
+                    qb.push("json_extract(tags, ").push_bind(path).push(") = ");
+                    crate::tag_value_bind!(qb, value);
+                }
+                TagFilter::NotEqual(value) => {
+                    qb.push("json_extract(tags, ")
+                        .push_bind(path)
+                        .push(") is not ");
+                    crate::tag_value_bind!(qb, value);
+                }
+                TagFilter::In(values) if values.is_empty() => {
+                    qb.push("false");
+                }
+                TagFilter::In(values) => {
+                    qb.push("json_extract(tags, ")
+                        .push_bind(path)
+                        .push(") in (");
+                    for (index, value) in values.iter().enumerate() {
+                        if index > 0 {
+                            qb.push(", ");
+                        }
+                        crate::tag_value_bind!(qb, value);
+                    }
+                    qb.push(")");
+                }
+                TagFilter::Prefix(prefix) => {
+                    qb.push("json_type(tags, ")
+                        .push_bind(path.clone())
+                        .push(") = 'text' and instr(json_extract(tags, ")
+                        .push_bind(path)
+                        .push("), ")
+                        .push_bind(prefix.as_ref())
+                        .push(") = 1");
+                }
+                TagFilter::Glob(pattern) => {
+                    qb.push("json_type(tags, ")
+                        .push_bind(path.clone())
+                        .push(") = 'text' and json_extract(tags, ")
+                        .push_bind(path)
+                        .push(") glob ")
+                        .push_bind(pattern.as_ref());
+                }
+            }
+        }
+    }
+}
+
+/// Pushes ` and (...)` with the conditions of the filters, when there are some.
+pub(crate) fn push_filters<'a>(
+    qb: &mut sqlx::QueryBuilder<'a, sqlx::Sqlite>,
+    filters: &'a [MetricFilter],
+) {
+    if filters.is_empty() {
+        return;
+    }
+    qb.push(" and (");
+    for (index, filter) in filters.iter().enumerate() {
+        if index > 0 {
+            qb.push(" or");
+        }
+        qb.push(" (");
+        filter.push_condition(qb);
+        qb.push(")");
+    }
+    qb.push(")");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{MetricFilter, TagFilter};
+    use crate::metrics::entity::MetricValue;
+    use crate::metrics::MetricHeader;
+
+    async fn setup() -> crate::Client {
+        let db = crate::Client::test().await;
+        for (name, address, room) in [
+            (
+                "atc-thermometer.temperature",
+                "A4:C1:38:00:00:01",
+                "kitchen",
+            ),
+            (
+                "atc-thermometer.temperature",
+                "A4:C1:38:00:00:02",
+                "bedroom",
+            ),
+            ("atc-thermometer.humidity", "A4:C1:38:00:00:01", "kitchen"),
+            ("miflora.temperature", "C4:7C:8D:00:00:01", "living-room"),
+        ] {
+            let header = MetricHeader::new(name)
+                .with_tag("address", address)
+                .with_tag("room", room);
+            crate::helper::create_metrics(
+                &db,
+                header,
+                [(10, MetricValue::gauge(20.0))].into_iter(),
+            )
+            .await;
+        }
+        let header =
+            MetricHeader::new("miflora.temperature").with_tag("address", "C4:7C:8D:00:00:02");
+        crate::helper::create_metrics(&db, header, [(10, MetricValue::gauge(20.0))].into_iter())
+            .await;
+        db
+    }
+
+    async fn find(db: &crate::Client, filter: MetricFilter) -> HashSet<(String, String)> {
+        crate::metrics::entity::find_latest::Command::new(&[filter], (0, 100), None)
+            .execute(db.as_ref())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|item| {
+                let address = item.header.tags.get("address").unwrap().as_text().unwrap();
+                (item.header.name.to_string(), address.to_string())
+            })
+            .collect()
+    }
+
+    fn expected(items: &[(&str, &str)]) -> HashSet<(String, String)> {
+        items
+            .iter()
+            .map(|(name, address)| (name.to_string(), address.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn should_filter_by_name_prefix() {
+        let db = setup().await;
+        let found = find(&db, MetricFilter::name_prefix("atc-thermometer.")).await;
+        assert_eq!(
+            found,
+            expected(&[
+                ("atc-thermometer.temperature", "A4:C1:38:00:00:01"),
+                ("atc-thermometer.temperature", "A4:C1:38:00:00:02"),
+                ("atc-thermometer.humidity", "A4:C1:38:00:00:01"),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn should_filter_by_tag_presence_and_value() {
+        let db = setup().await;
+
+        let found = find(
+            &db,
+            MetricFilter::new("miflora.temperature").with_tag("room", TagFilter::Any),
+        )
+        .await;
+        assert_eq!(
+            found,
+            expected(&[("miflora.temperature", "C4:7C:8D:00:00:01")])
+        );
+
+        let found = find(
+            &db,
+            MetricFilter::new("atc-thermometer.temperature")
+                .with_tag("room", TagFilter::NotEqual("kitchen".into())),
+        )
+        .await;
+        assert_eq!(
+            found,
+            expected(&[("atc-thermometer.temperature", "A4:C1:38:00:00:02")])
+        );
+
+        // a missing tag is not equal
+        let found = find(
+            &db,
+            MetricFilter::new("miflora.temperature")
+                .with_tag("room", TagFilter::NotEqual("living-room".into())),
+        )
+        .await;
+        assert_eq!(
+            found,
+            expected(&[("miflora.temperature", "C4:7C:8D:00:00:02")])
+        );
+    }
+
+    #[tokio::test]
+    async fn should_filter_by_tag_list() {
+        let db = setup().await;
+
+        let found = find(
+            &db,
+            MetricFilter::name_prefix("").with_tag(
+                "room",
+                TagFilter::In(vec!["bedroom".into(), "living-room".into()]),
+            ),
+        )
+        .await;
+        assert_eq!(
+            found,
+            expected(&[
+                ("atc-thermometer.temperature", "A4:C1:38:00:00:02"),
+                ("miflora.temperature", "C4:7C:8D:00:00:01"),
+            ])
+        );
+
+        let found = find(
+            &db,
+            MetricFilter::name_prefix("").with_tag("room", TagFilter::In(Vec::new())),
+        )
+        .await;
+        assert!(found.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_filter_by_text_pattern() {
+        let db = setup().await;
+
+        let found = find(
+            &db,
+            MetricFilter::name_prefix("").with_tag("address", TagFilter::Prefix("C4:7C:8D".into())),
+        )
+        .await;
+        assert_eq!(
+            found,
+            expected(&[
+                ("miflora.temperature", "C4:7C:8D:00:00:01"),
+                ("miflora.temperature", "C4:7C:8D:00:00:02"),
+            ])
+        );
+
+        let found = find(
+            &db,
+            MetricFilter::name_prefix("atc-thermometer.")
+                .with_tag("address", TagFilter::Glob("A4:C1:38:*:02".into()))
+                .with_tag("room", "bedroom"),
+        )
+        .await;
+        assert_eq!(
+            found,
+            expected(&[("atc-thermometer.temperature", "A4:C1:38:00:00:02")])
+        );
+    }
+}
@@ -4,12 +4,14 @@ use std::sync::Arc;
 
 pub mod aggr;
 pub mod entity;
+pub mod filter;
 pub mod macros;
+pub mod rollup;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct MetricHeader {
     pub name: MetricName,
-    #[serde(skip_serializing_if = "MetricTags::is_empty")]
+    #[serde(default, skip_serializing_if = "MetricTags::is_empty")]
     pub tags: MetricTags,
 }
 
@@ -65,6 +67,14 @@ impl MetricTagValue {
             _ => None,
         }
     }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(inner) => Some(inner.as_ref()),
+            Self::ArcText(inner) => Some(inner.as_ref()),
+            _ => None,
+        }
+    }
 }
 
 impl From<&'static str> for MetricTagValue {
@@ -127,9 +137,12 @@ impl Eq for MetricTagValue {}
 #[serde(transparent)]
 pub struct MetricTags(pub indexmap::IndexMap<Cow<'static, str>, MetricTagValue>);
 
+/// Tags are compared regardless of their order, so is the hash.
 impl std::hash::Hash for MetricTags {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.0.iter().for_each(|(key, value)| {
+        let mut entries = self.0.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(key, _)| *key);
+        entries.into_iter().for_each(|(key, value)| {
             key.hash(state);
             value.hash(state);
         });
@@ -137,6 +150,11 @@ impl std::hash::Hash for MetricTags {
 }
 
 impl MetricTags {
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&MetricTagValue> {
+        self.0.get(name)
+    }
+
     pub fn remove(&mut self, name: &str) -> Option<MetricTagValue> {
         self.0.shift_remove(name)
     }
@@ -182,4 +200,17 @@ impl MetricTags {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// JSON representation with the keys sorted, identifying a serie in the database.
+    pub(crate) fn to_canonical_json(&self) -> serde_json::Result<String> {
+        let mut entries = self.0.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(key, _)| *key);
+        let entries = serde_json::Map::from_iter(
+            entries
+                .into_iter()
+                .map(|(key, value)| serde_json::to_value(value).map(|v| (key.to_string(), v)))
+                .collect::<serde_json::Result<Vec<_>>>()?,
+        );
+        serde_json::to_string(&entries)
+    }
 }
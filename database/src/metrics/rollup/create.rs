@@ -0,0 +1,124 @@
+/// Value types that can be aggregated, the other ones are kept as raw points.
+const ROLLED_UP_TYPES: &[&str] = &["count", "gauge"];
+
+/// Rolls up the metrics older than `before` into buckets of `interval` seconds
+/// and removes the raw metrics that got aggregated.
+///
+/// Boolean metrics are neither rolled up nor removed, as the availability needs
+/// their transitions.
+pub struct Command {
+    before: u64,
+    interval: u64,
+}
+
+impl Command {
+    #[inline]
+    pub fn new(before: u64, interval: u64) -> Self {
+        Self { before, interval }
+    }
+
+    pub async fn execute(self, pool: &sqlx::Pool<sqlx::Sqlite>) -> sqlx::Result<u64> {
+        let interval = self.interval.max(1) as i64;
+        let mut tx = pool.begin().await?;
+
+        let mut qb = sqlx::QueryBuilder::new("insert into metric_rollups");
+        qb.push(" (series_id, timestamp, duration, type, min, max, sum, count)");
+        qb.push(" select series_id, (timestamp / ")
+            .push_bind(interval)
+            .push(") * ")
+            .push_bind(interval)
+            .push(" as bucket,");
+        qb.push(" ").push_bind(interval).push(",");
+        qb.push(" json_extract(value, '$.type') as type,");
+        qb.push(" min(json_extract(value, '$.value')),");
+        qb.push(" max(json_extract(value, '$.value')),");
+        qb.push(" sum(json_extract(value, '$.value')),");
+        qb.push(" count(timestamp)");
+        qb.push(" from metric_points");
+        qb.push(" where timestamp < ").push_bind(self.before as i64);
+        qb.push(" and json_extract(value, '$.type') in (");
+        let mut separated = qb.separated(", ");
+        for kind in ROLLED_UP_TYPES {
+            separated.push_bind(*kind);
+        }
+        separated.push_unseparated(")");
+        qb.push(" group by series_id, bucket, type");
+        let res = qb.build().execute(&mut *tx).await?;
+
+        crate::metrics::entity::delete::Command::new(self.before)
+            .with_types(ROLLED_UP_TYPES)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(res.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::entity::MetricValue;
+    use crate::metrics::MetricHeader;
+
+    #[tokio::test]
+    async fn should_rollup_old_metrics() {
+        let db = crate::Client::test().await;
+
+        let header = MetricHeader::new("foo").with_tag("host", "rpi");
+        crate::helper::create_metrics(
+            &db,
+            header.clone(),
+            (0..30).map(|index| (index * 10, MetricValue::gauge(index as f64))),
+        )
+        .await;
+
+        // metrics from 0 to 190 are rolled up in 2 buckets
+        let count = super::Command::new(200, 100)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+
+        let remaining: i64 = sqlx::query_scalar("select count(*) from metric_points")
+            .fetch_one(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(remaining, 10);
+
+        let rows: Vec<(i64, f64, f64, f64, i64)> = sqlx::query_as(
+            "select timestamp, cast(min as real), cast(max as real), cast(sum as real), count from metric_rollups order by timestamp",
+        )
+        .fetch_all(db.as_ref())
+        .await
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![(0, 0.0, 9.0, 45.0, 10), (100, 10.0, 19.0, 145.0, 10)]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_keep_bool_metrics() {
+        let db = crate::Client::test().await;
+
+        let header = MetricHeader::new("up").with_tag("target", "nas");
+        crate::helper::create_metrics(
+            &db,
+            header,
+            (0..10).map(|index| (index * 10, MetricValue::bool(index % 2 == 0))),
+        )
+        .await;
+
+        let count = super::Command::new(200, 100)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+
+        let remaining: i64 = sqlx::query_scalar("select count(*) from metric_points")
+            .fetch_one(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(remaining, 10);
+    }
+}
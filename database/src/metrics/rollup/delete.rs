@@ -0,0 +1,22 @@
+/// Removes the rollups older than `before`.
+pub struct Command {
+    before: u64,
+}
+
+impl Command {
+    #[inline]
+    pub fn new(before: u64) -> Self {
+        Self { before }
+    }
+
+    pub async fn execute<'c, E: sqlx::Executor<'c, Database = sqlx::Sqlite>>(
+        self,
+        executor: E,
+    ) -> sqlx::Result<u64> {
+        let res = sqlx::query("delete from metric_rollups where timestamp < $1")
+            .bind(self.before as i64)
+            .execute(executor)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
@@ -0,0 +1,6 @@
+//! Metrics older than the raw retention are aggregated by time bucket in the
+//! `metric_rollups` table, keeping the min, max, sum and count of every serie.
+//! Only count and gauge metrics are rolled up.
+
+pub mod create;
+pub mod delete;
@@ -0,0 +1,195 @@
+use std::time::Duration;
+
+use crate::alert;
+use crate::helper::now;
+use crate::metrics::entity::delete;
+use crate::metrics::rollup;
+
+const ONE_HOUR: u64 = 60 * 60;
+const ONE_DAY: u64 = ONE_HOUR * 24;
+
+fn default_raw_duration() -> u64 {
+    ONE_DAY * 7
+}
+
+fn default_rollup_duration() -> u64 {
+    ONE_DAY * 365
+}
+
+fn default_rollup_interval() -> u64 {
+    ONE_HOUR
+}
+
+fn default_interval() -> u64 {
+    ONE_HOUR
+}
+
+/// Retention policy of the metrics, all the durations are in seconds.
+///
+/// Raw metrics older than `raw_duration` are aggregated in buckets of `rollup_interval`,
+/// which are then kept for `rollup_duration`. Boolean metrics are not rolled up, their raw
+/// points are kept for `rollup_duration` so that the availability stays exact. The alert
+/// transitions are kept for `rollup_duration` as well, except for the alerts still firing.
+#[derive(Clone, Copy, Debug, serde::Deserialize)]
+pub struct Config {
+    #[serde(default = "default_raw_duration")]
+    raw_duration: u64,
+    #[serde(default = "default_rollup_duration")]
+    rollup_duration: u64,
+    #[serde(default = "default_rollup_interval")]
+    rollup_interval: u64,
+    /// Interval between two executions of the retention task.
+    #[serde(default = "default_interval")]
+    interval: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            raw_duration: default_raw_duration(),
+            rollup_duration: default_rollup_duration(),
+            rollup_interval: default_rollup_interval(),
+            interval: default_interval(),
+        }
+    }
+}
+
+impl Config {
+    /// Only complete buckets get rolled up.
+    fn rollup_before(&self, current: u64) -> u64 {
+        let interval = self.rollup_interval.max(1);
+        let before = current.saturating_sub(self.raw_duration);
+        before - before % interval
+    }
+
+    pub(crate) async fn execute(&self, client: &crate::Client) -> sqlx::Result<()> {
+        self.execute_at(client, now()).await
+    }
+
+    async fn execute_at(&self, client: &crate::Client, current: u64) -> sqlx::Result<()> {
+        let count = rollup::create::Command::new(self.rollup_before(current), self.rollup_interval)
+            .execute(client.as_ref())
+            .await?;
+        tracing::debug!(message = "rolled up metrics", count = count);
+        let count = rollup::delete::Command::new(current.saturating_sub(self.rollup_duration))
+            .execute(client.as_ref())
+            .await?;
+        tracing::debug!(message = "removed expired rollups", count = count);
+        // only the boolean points remain after the rollup
+        let count = delete::Command::new(current.saturating_sub(self.rollup_duration))
+            .execute(client.as_ref())
+            .await?;
+        tracing::debug!(message = "removed expired boolean metrics", count = count);
+        let count = alert::delete::Command::new(current.saturating_sub(self.rollup_duration))
+            .execute(client.as_ref())
+            .await?;
+        tracing::debug!(message = "removed expired alert transitions", count = count);
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "retention", skip_all)]
+    pub(crate) async fn run(self, client: crate::Client) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.interval.max(1)));
+        loop {
+            interval.tick().await;
+            if let Err(error) = self.execute(&client).await {
+                tracing::error!(message = "unable to apply retention policy", cause = %error);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alert::{AlertStatus, AlertTransition};
+    use crate::metrics::entity::MetricValue;
+    use crate::metrics::MetricHeader;
+
+    async fn count_points(db: &crate::Client, kind: &str) -> i64 {
+        sqlx::query_scalar(
+            "select count(*) from metric_points where json_extract(value, '$.type') = ?",
+        )
+        .bind(kind)
+        .fetch_one(db.as_ref())
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_keep_bool_metrics_until_rollup_expiry() {
+        let db = crate::Client::test().await;
+        let config = super::Config {
+            raw_duration: 100,
+            rollup_duration: 1000,
+            rollup_interval: 10,
+            ..Default::default()
+        };
+
+        crate::helper::create_metrics(
+            &db,
+            MetricHeader::new("up"),
+            (0..10).map(|index| (index * 10, MetricValue::bool(index % 2 == 0))),
+        )
+        .await;
+        crate::helper::create_metrics(
+            &db,
+            MetricHeader::new("cpu"),
+            (0..10).map(|index| (index * 10, MetricValue::gauge(index as f64))),
+        )
+        .await;
+
+        // the gauges get rolled up, the booleans remain
+        config.execute_at(&db, 500).await.unwrap();
+        assert_eq!(count_points(&db, "gauge").await, 0);
+        assert_eq!(count_points(&db, "bool").await, 10);
+
+        // expired with the rollups
+        config.execute_at(&db, 1050).await.unwrap();
+        assert_eq!(count_points(&db, "bool").await, 5);
+    }
+
+    #[tokio::test]
+    async fn should_remove_expired_alert_transitions() {
+        let db = crate::Client::test().await;
+        let config = super::Config {
+            rollup_duration: 1000,
+            ..Default::default()
+        };
+        for (status, timestamp) in [
+            (AlertStatus::Firing, 10),
+            (AlertStatus::Resolved, 20),
+            (AlertStatus::Firing, 1500),
+        ] {
+            crate::alert::create::Command::new(&AlertTransition {
+                rule: "plant".into(),
+                header: MetricHeader::new("miflora.moisture"),
+                status,
+                value: None,
+                since: timestamp,
+                timestamp,
+            })
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        }
+
+        config.execute_at(&db, 2000).await.unwrap();
+        let found = crate::alert::list::Command::new(0, None)
+            .execute(db.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].timestamp, 1500);
+    }
+
+    #[test]
+    fn should_only_rollup_complete_buckets() {
+        let config = super::Config {
+            raw_duration: 100,
+            rollup_interval: 60,
+            ..Default::default()
+        };
+        assert_eq!(config.rollup_before(1000), 900);
+        assert_eq!(config.rollup_before(50), 0);
+    }
+}
@@ -1,8 +1,12 @@
 use std::sync::Arc;
 
+use anyhow::Context;
+
 use axum::Extension;
 use tower_http::trace::TraceLayer;
 
+use crate::router::prometheus;
+use crate::service::alert;
 use crate::service::dashboard::Dashboard;
 
 fn default_host() -> std::net::IpAddr {
@@ -27,6 +31,10 @@ pub(crate) struct Config {
     assets_path: String,
     #[serde(default)]
     dashboard: Dashboard,
+    #[serde(default)]
+    alerting: alert::Config,
+    #[serde(default)]
+    prometheus: prometheus::Config,
 }
 
 impl Default for Config {
@@ -36,6 +44,8 @@ impl Default for Config {
             port: default_port(),
             assets_path: default_assets_path(),
             dashboard: Default::default(),
+            alerting: Default::default(),
+            prometheus: Default::default(),
         }
     }
 }
@@ -43,24 +53,32 @@ impl Default for Config {
 impl Config {
     pub async fn build(self) -> anyhow::Result<Application> {
         Ok(Application {
+            alerting: self
+                .alerting
+                .build(&self.dashboard)
+                .context("building alerting")?,
             assets_path: self.assets_path,
             dashboard: Arc::new(self.dashboard),
+            prometheus: self.prometheus,
             socket_address: std::net::SocketAddr::from((self.host, self.port)),
         })
     }
 }
 
 pub(crate) struct Application {
+    alerting: alert::Engine,
     assets_path: String,
     dashboard: Arc<Dashboard>,
+    prometheus: prometheus::Config,
     socket_address: std::net::SocketAddr,
 }
 
 impl Application {
     fn router(&self, database: chezmoi_database::Client) -> axum::Router {
-        crate::router::create(&self.assets_path)
+        crate::router::create(&self.assets_path, self.prometheus)
             .layer(Extension(database))
             .layer(Extension(self.dashboard.clone()))
+            .layer(Extension(self.alerting.rules()))
             .layer(TraceLayer::new_for_http())
     }
 
@@ -68,7 +86,9 @@ impl Application {
         tracing::debug!("binding socket to {}", self.socket_address);
         let listener = tokio::net::TcpListener::bind(self.socket_address).await?;
         tracing::info!("listening on {}", self.socket_address);
-        axum::serve(listener, self.router(database)).await?;
+        let router = self.router(database.clone());
+        let (served, _) = tokio::join!(axum::serve(listener, router), self.alerting.run(database));
+        served?;
         Ok(())
     }
 }
@@ -42,7 +42,13 @@ async fn main() -> anyhow::Result<()> {
     let agent = agent.build().await.context("building agent")?;
     let app = server.build().await.context("building server")?;
 
-    let (agent, app) = tokio::join!(agent.run(database.clone()), app.run(database));
+    let agent = agent.with_sink(chezmoi_agent::sink::database::Sink::new(database.clone()));
+
+    let (agent, app, _) = tokio::join!(
+        agent.run(),
+        app.run(database.clone()),
+        database.run_retention()
+    );
     tracing::debug!("agent success={}", agent.is_ok());
     tracing::debug!("app success={}", app.is_ok());
 
@@ -0,0 +1,45 @@
+use std::borrow::Cow;
+
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+
+#[derive(Debug, serde::Serialize)]
+struct ErrorBody<'a> {
+    message: &'a str,
+}
+
+#[derive(Debug)]
+pub struct Error {
+    status: StatusCode,
+    message: Cow<'static, str>,
+}
+
+impl Error {
+    pub fn new(status: StatusCode, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+}
+
+impl From<chezmoi_database::sqlx::Error> for Error {
+    fn from(value: chezmoi_database::sqlx::Error) -> Self {
+        tracing::error!(message = "something went wrong with database", cause = %value);
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong...")
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> axum::response::Response {
+        let body = ErrorBody {
+            message: self.message.as_ref(),
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
@@ -0,0 +1,100 @@
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use chezmoi_database::metrics::entity::create::{self, MAX_BATCH_SIZE};
+use chezmoi_database::metrics::entity::Metric;
+
+use crate::router::api::error::Error;
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct Response {
+    count: u64,
+}
+
+fn validate(batch: &[Metric]) -> Result<(), Error> {
+    if batch.is_empty() {
+        return Err(Error::bad_request("the batch should not be empty"));
+    }
+    if batch.len() > MAX_BATCH_SIZE {
+        return Err(Error::bad_request(format!(
+            "the batch should not contain more than {MAX_BATCH_SIZE} metrics"
+        )));
+    }
+    if let Some(index) = batch
+        .iter()
+        .position(|metric| metric.header.name.trim().is_empty())
+    {
+        return Err(Error::bad_request(format!(
+            "the metric at index {index} has an empty name"
+        )));
+    }
+    Ok(())
+}
+
+pub(super) async fn handle(
+    Extension(database): Extension<chezmoi_database::Client>,
+    Json(batch): Json<Vec<Metric>>,
+) -> Result<(StatusCode, Json<Response>), Error> {
+    validate(&batch)?;
+    let count = create::Command::new(&batch)
+        .execute(database.as_ref())
+        .await?;
+    tracing::debug!(message = "stored events", count = count);
+    Ok((StatusCode::CREATED, Json(Response { count })))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use chezmoi_database::metrics::entity::{Metric, MetricValue};
+    use chezmoi_database::metrics::MetricHeader;
+
+    fn metrics(count: usize) -> Vec<Metric> {
+        (0..count)
+            .map(|index| Metric {
+                timestamp: index as u64,
+                header: MetricHeader::new("system.cpu"),
+                value: MetricValue::gauge(12.5),
+            })
+            .collect()
+    }
+
+    fn assert_bad_request(batch: &[Metric]) {
+        let error = super::validate(batch).unwrap_err();
+        assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn should_accept_valid_batch() {
+        assert!(super::validate(&metrics(1)).is_ok());
+        assert!(super::validate(&metrics(super::MAX_BATCH_SIZE)).is_ok());
+    }
+
+    #[test]
+    fn should_reject_empty_batch() {
+        assert_bad_request(&[]);
+    }
+
+    #[test]
+    fn should_reject_batch_over_limit() {
+        assert_bad_request(&metrics(super::MAX_BATCH_SIZE + 1));
+    }
+
+    #[test]
+    fn should_reject_metrics_without_name() {
+        let mut batch = metrics(3);
+        batch[1].header = MetricHeader::new(" ");
+        assert_bad_request(&batch);
+    }
+
+    #[test]
+    fn should_reject_non_finite_values() {
+        // json has no representation for infinite gauges
+        let payload = |value: &str| {
+            format!(r#"[{{"name":"system.cpu","value":{{"type":"gauge","value":{value}}}}}]"#)
+        };
+        assert!(serde_json::from_str::<Vec<Metric>>(&payload("12.5")).is_ok());
+        assert!(serde_json::from_str::<Vec<Metric>>(&payload("1e400")).is_err());
+        assert!(serde_json::from_str::<Vec<Metric>>(&payload("null")).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+use axum::extract::Query;
+use axum::{Extension, Json};
+use chezmoi_database::metrics::aggr::{self, MetricAggr};
+
+use super::QueryParams;
+use crate::router::api::error::Error;
+
+pub(super) async fn handle(
+    Extension(database): Extension<chezmoi_database::Client>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<Json<Vec<MetricAggr>>, Error> {
+    let params = QueryParams::try_from(params)?;
+    let filters = params.filters();
+    let metrics = aggr::list::Command::new(&filters, params.window()?, params.divisions()?)
+        .with_group_by(params.group_by())
+        .with_rate(params.rate())
+        .execute(database.as_ref())
+        .await?;
+    Ok(Json(metrics))
+}
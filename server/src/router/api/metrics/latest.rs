@@ -0,0 +1,18 @@
+use axum::extract::Query;
+use axum::{Extension, Json};
+use chezmoi_database::metrics::entity::{find_latest, Metric};
+
+use super::QueryParams;
+use crate::router::api::error::Error;
+
+pub(super) async fn handle(
+    Extension(database): Extension<chezmoi_database::Client>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<Json<Vec<Metric>>, Error> {
+    let params = QueryParams::try_from(params)?;
+    let filters = params.filters();
+    let metrics = find_latest::Command::new(&filters, params.window()?, params.limit)
+        .execute(database.as_ref())
+        .await?;
+    Ok(Json(metrics))
+}
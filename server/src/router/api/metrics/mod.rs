@@ -0,0 +1,296 @@
+use std::str::FromStr;
+
+use axum::routing::{get, post};
+use chezmoi_database::helper::now;
+use chezmoi_database::metrics::aggr::list::GroupBy;
+use chezmoi_database::metrics::filter::{MetricFilter, NameFilter, TagFilter};
+
+use super::error::Error;
+
+mod create;
+mod history;
+mod latest;
+
+const DEFAULT_DURATION: u64 = 60 * 60 * 24 * 7;
+const DEFAULT_DIVISIONS: usize = 30;
+const TAG_PREFIX: &str = "tag.";
+const NAME_PREFIX: &str = "name:prefix";
+
+pub(super) fn create() -> axum::Router {
+    axum::Router::new()
+        .route("/", post(create::handle))
+        .route("/latest", get(latest::handle))
+        .route("/history", get(history::handle))
+}
+
+fn parse_param<V: FromStr>(key: &str, value: &str) -> Result<V, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::bad_request(format!("invalid value {value:?} for {key:?}")))
+}
+
+fn parse_tag_filter(key: &str, operator: Option<&str>, value: String) -> Result<TagFilter, Error> {
+    match operator {
+        None => Ok(TagFilter::Equal(value.into())),
+        Some("any") => Ok(TagFilter::Any),
+        Some("ne") => Ok(TagFilter::NotEqual(value.into())),
+        Some("in") => Ok(TagFilter::In(
+            value
+                .split(',')
+                .filter(|item| !item.is_empty())
+                .map(|item| item.to_owned().into())
+                .collect(),
+        )),
+        Some("prefix") => Ok(TagFilter::Prefix(value.into())),
+        Some("glob") => Ok(TagFilter::Glob(value.into())),
+        Some(_) => Err(Error::bad_request(format!("unknown parameter {key:?}"))),
+    }
+}
+
+/// Parameters accepted by the metrics query endpoints.
+///
+/// `name` can be repeated to query several metrics and tag filters are provided
+/// with `tag.<key>=<value>`, for example `?name=miflora.moisture&tag.address=00:00:00:00:00`.
+/// Every requested name is matched against the same set of tags.
+///
+/// Names can also be matched with `name:prefix=<prefix>` and tags support the following operators:
+/// - `tag.<key>:any` for any value, the parameter value being ignored,
+/// - `tag.<key>:ne=<value>` for another value or a missing tag,
+/// - `tag.<key>:in=<first>,<second>` for one of the values,
+/// - `tag.<key>:prefix=<prefix>` and `tag.<key>:glob=<pattern>` for text values.
+///
+/// When building the history, `group_by=<key>` can be repeated to merge the series sharing
+/// the same values for those tags, an empty value merging all the series of a name.
+/// With `rate=true`, the count metrics are considered as counters and provide their
+/// increase and per second rate over each division.
+#[derive(Debug, Default)]
+pub(crate) struct QueryParams {
+    names: Vec<NameFilter>,
+    tags: Vec<(String, TagFilter)>,
+    from: Option<u64>,
+    to: Option<u64>,
+    /// Only used when looking for the latest values
+    limit: Option<usize>,
+    /// Only used when building the history
+    divisions: Option<usize>,
+    /// Only used when building the history
+    group_by: Option<Vec<String>>,
+    /// Only used when building the history
+    rate: Option<bool>,
+}
+
+impl TryFrom<Vec<(String, String)>> for QueryParams {
+    type Error = Error;
+
+    fn try_from(list: Vec<(String, String)>) -> Result<Self, Self::Error> {
+        let mut res = Self::default();
+        for (key, value) in list {
+            match key.as_str() {
+                "name" => res.names.push(NameFilter::Equal(value.into())),
+                NAME_PREFIX => res.names.push(NameFilter::Prefix(value.into())),
+                "from" => res.from = Some(parse_param(&key, &value)?),
+                "to" => res.to = Some(parse_param(&key, &value)?),
+                "limit" => res.limit = Some(parse_param(&key, &value)?),
+                "divisions" => res.divisions = Some(parse_param(&key, &value)?),
+                "rate" => res.rate = Some(parse_param(&key, &value)?),
+                "group_by" => {
+                    let group_by = res.group_by.get_or_insert_with(Vec::new);
+                    if !value.is_empty() {
+                        group_by.push(value);
+                    }
+                }
+                other => match other.strip_prefix(TAG_PREFIX) {
+                    Some(tag) => {
+                        let (tag, operator) = match tag.split_once(':') {
+                            Some((tag, operator)) => (tag, Some(operator)),
+                            None => (tag, None),
+                        };
+                        if tag.is_empty() {
+                            return Err(Error::bad_request(format!("unknown parameter {key:?}")));
+                        }
+                        let filter = parse_tag_filter(&key, operator, value)?;
+                        res.tags.push((tag.to_owned(), filter));
+                    }
+                    None => return Err(Error::bad_request(format!("unknown parameter {key:?}"))),
+                },
+            }
+        }
+        if res.names.is_empty() && !res.tags.is_empty() {
+            return Err(Error::bad_request(
+                "tag filters require at least one metric name",
+            ));
+        }
+        Ok(res)
+    }
+}
+
+impl QueryParams {
+    fn filters(&self) -> Vec<MetricFilter> {
+        self.names
+            .iter()
+            .map(|name| MetricFilter {
+                name: name.clone(),
+                tags: self
+                    .tags
+                    .iter()
+                    .map(|(tag, filter)| (tag.clone().into(), filter.clone()))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn window(&self) -> Result<(u64, u64), Error> {
+        let to = self.to.unwrap_or_else(now);
+        let from = self
+            .from
+            .unwrap_or_else(|| to.saturating_sub(DEFAULT_DURATION));
+        if from >= to {
+            return Err(Error::bad_request("\"from\" should be before \"to\""));
+        }
+        Ok((from, to))
+    }
+
+    fn group_by(&self) -> GroupBy {
+        match self.group_by {
+            Some(ref tags) => GroupBy::Tags(tags.iter().cloned().map(Into::into).collect()),
+            None => GroupBy::Serie,
+        }
+    }
+
+    fn rate(&self) -> bool {
+        self.rate.unwrap_or(false)
+    }
+
+    fn divisions(&self) -> Result<usize, Error> {
+        match self.divisions {
+            Some(0) => Err(Error::bad_request("\"divisions\" should be greater than 0")),
+            Some(value) => Ok(value),
+            None => Ok(DEFAULT_DIVISIONS),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use chezmoi_database::metrics::aggr::list::GroupBy;
+    use chezmoi_database::metrics::filter::{NameFilter, TagFilter};
+
+    use super::QueryParams;
+
+    fn parse(list: &[(&str, &str)]) -> Result<QueryParams, super::Error> {
+        QueryParams::try_from(
+            list.iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn assert_bad_request(res: Result<impl std::fmt::Debug, super::Error>) {
+        let error = res.unwrap_err();
+        assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn should_parse_names_and_tags() {
+        let params = parse(&[
+            ("name", "miflora.moisture"),
+            ("name:prefix", "host.disk."),
+            ("tag.address", "C4:7C:8D:6A:3E:1F"),
+            ("tag.mount:ne", "/boot"),
+            ("tag.device:in", "sda,,sdb"),
+            ("tag.host:any", ""),
+            ("tag.interface:glob", "eth*"),
+        ])
+        .unwrap();
+        assert_eq!(
+            params.names,
+            vec![
+                NameFilter::Equal("miflora.moisture".into()),
+                NameFilter::Prefix("host.disk.".into()),
+            ]
+        );
+        assert_eq!(
+            params.tags,
+            vec![
+                (
+                    "address".to_string(),
+                    TagFilter::Equal("C4:7C:8D:6A:3E:1F".into())
+                ),
+                ("mount".to_string(), TagFilter::NotEqual("/boot".into())),
+                (
+                    "device".to_string(),
+                    TagFilter::In(vec!["sda".into(), "sdb".into()])
+                ),
+                ("host".to_string(), TagFilter::Any),
+                ("interface".to_string(), TagFilter::Glob("eth*".into())),
+            ]
+        );
+        assert_eq!(params.filters().len(), 2);
+    }
+
+    #[test]
+    fn should_reject_unknown_parameters() {
+        assert_bad_request(parse(&[("name", "foo"), ("unknown", "bar")]));
+        assert_bad_request(parse(&[("name", "foo"), ("tag.", "bar")]));
+        assert_bad_request(parse(&[("name", "foo"), ("tag.:any", "")]));
+        assert_bad_request(parse(&[("name", "foo"), ("tag.address:like", "bar")]));
+        // tags without a name would match every metric
+        assert_bad_request(parse(&[("tag.address", "bar")]));
+    }
+
+    #[test]
+    fn should_reject_invalid_values() {
+        assert_bad_request(parse(&[("from", "yesterday")]));
+        assert_bad_request(parse(&[("limit", "-1")]));
+        assert_bad_request(parse(&[("rate", "yes")]));
+    }
+
+    #[test]
+    fn should_validate_window() {
+        let params = parse(&[("from", "100"), ("to", "200")]).unwrap();
+        assert_eq!(params.window().unwrap(), (100, 200));
+
+        let params = parse(&[("to", "1000000")]).unwrap();
+        assert_eq!(
+            params.window().unwrap(),
+            (1000000 - super::DEFAULT_DURATION, 1000000)
+        );
+
+        assert_bad_request(parse(&[("from", "200"), ("to", "200")]).unwrap().window());
+        assert_bad_request(parse(&[("from", "300"), ("to", "200")]).unwrap().window());
+    }
+
+    #[test]
+    fn should_validate_divisions() {
+        let params = parse(&[]).unwrap();
+        assert_eq!(params.divisions().unwrap(), super::DEFAULT_DIVISIONS);
+        let params = parse(&[("divisions", "10")]).unwrap();
+        assert_eq!(params.divisions().unwrap(), 10);
+        assert_bad_request(parse(&[("divisions", "0")]).unwrap().divisions());
+    }
+
+    #[test]
+    fn should_parse_group_by_and_rate() {
+        let params = parse(&[]).unwrap();
+        assert_eq!(params.group_by(), GroupBy::Serie);
+        assert!(!params.rate());
+
+        let params = parse(&[
+            ("group_by", "address"),
+            ("group_by", "room"),
+            ("rate", "true"),
+        ])
+        .unwrap();
+        assert_eq!(
+            params.group_by(),
+            GroupBy::Tags(vec!["address".into(), "room".into()])
+        );
+        assert!(params.rate());
+
+        // an empty value merges all the series
+        let params = parse(&[("group_by", "")]).unwrap();
+        assert_eq!(params.group_by(), GroupBy::Tags(Vec::new()));
+    }
+}
@@ -1,7 +1,11 @@
 use axum::routing::head;
 
+mod error;
+mod metrics;
 mod status;
 
 pub(super) fn create() -> axum::Router {
-    axum::Router::new().route("/status", head(status::handle))
+    axum::Router::new()
+        .route("/status", head(status::handle))
+        .nest("/metrics", metrics::create())
 }
@@ -1,10 +1,12 @@
 mod api;
 mod asset;
+pub(crate) mod prometheus;
 mod ui;
 
-pub(super) fn create(assets_path: &str) -> axum::Router {
+pub(super) fn create(assets_path: &str, prometheus: prometheus::Config) -> axum::Router {
     axum::Router::new()
         .nest("/api", api::create())
         .merge(asset::router(assets_path))
+        .merge(prometheus::router(prometheus))
         .merge(ui::create())
 }
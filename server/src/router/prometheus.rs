@@ -0,0 +1,279 @@
+//! Exposes the latest value of every serie in the Prometheus text format.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Extension;
+use chezmoi_database::helper::now;
+use chezmoi_database::metrics::entity::{find_latest, Metric, MetricValue};
+use chezmoi_database::metrics::{MetricTagValue, MetricTags};
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+fn default_staleness() -> u64 {
+    // the bluetooth sensors only report every hour
+    60 * 60 * 3
+}
+
+/// Configuration of the Prometheus endpoint.
+///
+/// ```toml
+/// [server.prometheus]
+/// staleness = 10800
+/// ```
+#[derive(Clone, Copy, Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    /// Duration in seconds after which a serie without new value is not exported anymore.
+    #[serde(default = "default_staleness")]
+    staleness: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            staleness: default_staleness(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Counter,
+    Gauge,
+}
+
+impl Kind {
+    const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Counter => "counter",
+            Self::Gauge => "gauge",
+        }
+    }
+}
+
+/// Replaces the characters not allowed by Prometheus with an underscore.
+fn sanitize(value: &str, allow_colon: bool) -> String {
+    let mut res = String::with_capacity(value.len() + 1);
+    if value.starts_with(|c: char| c.is_ascii_digit()) {
+        res.push('_');
+    }
+    res.extend(value.chars().map(|c| {
+        if c.is_ascii_alphanumeric() || c == '_' || (allow_colon && c == ':') {
+            c
+        } else {
+            '_'
+        }
+    }));
+    res
+}
+
+fn metric_name(name: &str, kind: Kind) -> String {
+    let mut res = sanitize(name, true);
+    if kind == Kind::Counter && !res.ends_with("_total") {
+        res.push_str("_total");
+    }
+    res
+}
+
+fn label_value(value: &MetricTagValue) -> String {
+    let value = match value {
+        MetricTagValue::Text(inner) => inner.to_string(),
+        MetricTagValue::ArcText(inner) => inner.to_string(),
+        MetricTagValue::Float(inner) => inner.to_string(),
+        MetricTagValue::Int(inner) => inner.to_string(),
+        MetricTagValue::Boolean(inner) => inner.to_string(),
+    };
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Builds the label set of a sample.
+///
+/// The names starting with `__` are reserved by Prometheus and the tags whose
+/// sanitized names collide with a previous one, like `a.b` and `a_b`, are skipped.
+fn labels(tags: &MetricTags) -> String {
+    let mut names = HashSet::new();
+    let labels = tags
+        .entries()
+        .filter_map(|(key, value)| {
+            let name = sanitize(key, false);
+            if name.is_empty() || name.starts_with("__") || !names.insert(name.clone()) {
+                return None;
+            }
+            Some(format!("{name}=\"{}\"", label_value(value)))
+        })
+        .collect::<Vec<_>>();
+    if labels.is_empty() {
+        return String::new();
+    }
+    format!("{{{}}}", labels.join(","))
+}
+
+/// Formats a sample value, the exposition format spelling the non finite values its own way.
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        String::from("NaN")
+    } else if value.is_infinite() {
+        String::from(if value > 0.0 { "+Inf" } else { "-Inf" })
+    } else {
+        value.to_string()
+    }
+}
+
+fn sample(value: &MetricValue) -> (Kind, f64) {
+    match value {
+        MetricValue::Count { value } => (Kind::Counter, *value as f64),
+        MetricValue::Gauge { value } => (Kind::Gauge, *value),
+        MetricValue::Bool { value } => (Kind::Gauge, if *value { 1.0 } else { 0.0 }),
+    }
+}
+
+fn render(metrics: Vec<Metric>) -> String {
+    let mut families: BTreeMap<String, (Kind, Vec<(String, f64)>)> = BTreeMap::new();
+    for metric in metrics {
+        let (kind, value) = sample(&metric.value);
+        let name = metric_name(metric.header.name.as_ref(), kind);
+        let family = families.entry(name).or_insert_with(|| (kind, Vec::new()));
+        // a family can only have one type
+        if family.0 == kind {
+            family.1.push((labels(&metric.header.tags), value));
+        }
+    }
+
+    let mut output = String::new();
+    for (name, (kind, mut samples)) in families {
+        samples.sort_by(|(first, _), (second, _)| first.cmp(second));
+        // distinct series can end up with the same labels once sanitized
+        samples.dedup_by(|(first, _), (second, _)| first == second);
+        let _ = writeln!(output, "# TYPE {name} {}", kind.as_str());
+        for (labels, value) in samples {
+            let _ = writeln!(output, "{name}{labels} {}", format_value(value));
+        }
+    }
+    output
+}
+
+async fn handle(
+    Extension(database): Extension<chezmoi_database::Client>,
+    Extension(config): Extension<Config>,
+) -> impl IntoResponse {
+    let now = now();
+    match find_latest::Command::new(&[], (now.saturating_sub(config.staleness), now), None)
+        .execute(database.as_ref())
+        .await
+    {
+        Ok(metrics) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, CONTENT_TYPE)],
+            render(metrics),
+        ),
+        Err(inner) => {
+            tracing::error!(message = "unable to fetch latest metrics", cause = %inner);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                [(header::CONTENT_TYPE, CONTENT_TYPE)],
+                String::default(),
+            )
+        }
+    }
+}
+
+pub(super) fn router(config: Config) -> axum::Router {
+    axum::Router::new()
+        .route("/metrics", get(handle))
+        .layer(Extension(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use chezmoi_database::metrics::entity::{Metric, MetricValue};
+    use chezmoi_database::metrics::MetricHeader;
+
+    #[test]
+    fn should_sanitize_names() {
+        assert_eq!(
+            super::sanitize("system.cpu-usage", false),
+            "system_cpu_usage"
+        );
+        assert_eq!(super::sanitize("a:b", false), "a_b");
+        assert_eq!(super::sanitize("a:b", true), "a:b");
+        assert_eq!(super::sanitize("1st", false), "_1st");
+    }
+
+    #[test]
+    fn should_build_labels() {
+        let header = MetricHeader::new("foo");
+        assert_eq!(super::labels(&header.tags), "");
+
+        let header = MetricHeader::new("foo")
+            .with_tag("host.name", "rpi")
+            .with_tag("quote", "a \"b\"");
+        assert_eq!(
+            super::labels(&header.tags),
+            "{host_name=\"rpi\",quote=\"a \\\"b\\\"\"}"
+        );
+    }
+
+    #[test]
+    fn should_skip_colliding_and_reserved_labels() {
+        let header = MetricHeader::new("foo")
+            .with_tag("a.b", "first")
+            .with_tag("a_b", "second")
+            .with_tag("__name__", "reserved");
+        assert_eq!(super::labels(&header.tags), "{a_b=\"first\"}");
+
+        let header = MetricHeader::new("foo").with_tag("__name__", "reserved");
+        assert_eq!(super::labels(&header.tags), "");
+    }
+
+    #[test]
+    fn should_format_non_finite_values() {
+        assert_eq!(super::format_value(12.5), "12.5");
+        assert_eq!(super::format_value(-3.0), "-3");
+        assert_eq!(super::format_value(f64::INFINITY), "+Inf");
+        assert_eq!(super::format_value(f64::NEG_INFINITY), "-Inf");
+        assert_eq!(super::format_value(f64::NAN), "NaN");
+    }
+
+    #[test]
+    fn should_render_families() {
+        let metrics = vec![
+            Metric {
+                timestamp: 0,
+                header: MetricHeader::new("network.received").with_tag("interface", "eth0"),
+                value: MetricValue::count(42),
+            },
+            Metric {
+                timestamp: 0,
+                header: MetricHeader::new("system.cpu").with_tag("a.b", "x"),
+                value: MetricValue::gauge(12.5),
+            },
+            // same labels once sanitized
+            Metric {
+                timestamp: 0,
+                header: MetricHeader::new("system.cpu").with_tag("a_b", "x"),
+                value: MetricValue::gauge(13.5),
+            },
+            Metric {
+                timestamp: 0,
+                header: MetricHeader::new("probe.up"),
+                value: MetricValue::bool(true),
+            },
+        ];
+        assert_eq!(
+            super::render(metrics),
+            "# TYPE network_received_total counter
+network_received_total{interface=\"eth0\"} 42
+# TYPE probe_up gauge
+probe_up 1
+# TYPE system_cpu gauge
+system_cpu{a_b=\"x\"} 12.5
+"
+        );
+    }
+}
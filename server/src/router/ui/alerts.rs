@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use axum::response::Html;
+use axum::Extension;
+use chezmoi_client::view::prelude::View;
+use chezmoi_database::alert;
+use chezmoi_database::helper::now;
+
+use super::error::Error;
+use crate::service::alert::rule::Rule;
+
+/// Resolved alerts are displayed for a week.
+const RESOLVED_DURATION: u64 = 60 * 60 * 24 * 7;
+
+pub(super) async fn handle(
+    Extension(rules): Extension<Arc<Vec<Rule>>>,
+    Extension(database): Extension<chezmoi_database::Client>,
+) -> Result<Html<String>, Error> {
+    let since = now().saturating_sub(RESOLVED_DURATION);
+    let latest = alert::find_latest::Command
+        .execute(database.as_ref())
+        .await?;
+    let history = alert::list::Command::new(since, None)
+        .execute(database.as_ref())
+        .await?;
+
+    let page = crate::service::alert::view::build_view(&rules, latest, history, since);
+
+    Ok(Html(page.render()))
+}
@@ -7,7 +7,9 @@ use chezmoi_client::view::dashboard::TimePickerDuration;
 use chezmoi_client::view::prelude::View;
 use chezmoi_database::helper::now;
 use chezmoi_database::metrics::aggr;
-use chezmoi_database::metrics::entity::find_latest;
+use chezmoi_database::metrics::entity::{availability, find_latest};
+use chezmoi_database::metrics::filter::MetricFilter;
+use chezmoi_database::metrics::MetricHeader;
 
 use super::error::Error;
 use crate::service::dashboard::{BuilderContext, Dashboard};
@@ -64,22 +66,42 @@ impl QueryParams {
     }
 }
 
+fn filters(headers: impl IntoIterator<Item = MetricHeader>) -> Vec<MetricFilter> {
+    headers.into_iter().map(MetricFilter::from).collect()
+}
+
 pub(super) async fn handle(
     Extension(dashboard): Extension<Arc<Dashboard>>,
     Extension(database): Extension<chezmoi_database::Client>,
     Query(params): Query<QueryParams>,
 ) -> Result<Html<String>, Error> {
     let mut ctx = BuilderContext::new(params.duration(), params.window());
-    let latest_headers = dashboard.collect_latest_metrics();
-    let history_headers = dashboard.collect_history_metrics();
-    let latests = find_latest::Command::new(&latest_headers, params.window(), None)
+    let latest_filters = filters(dashboard.collect_latest_metrics());
+    let history_filters = filters(dashboard.collect_history_metrics());
+    let availability_filters = filters(dashboard.collect_availability_metrics());
+    let latests = find_latest::Command::new(&latest_filters, params.window(), None)
         .execute(database.as_ref())
         .await?;
-    let history = aggr::list::Command::new(&history_headers, params.window(), 30)
+    let history = aggr::list::Command::new(&history_filters, params.window(), 30)
         .execute(database.as_ref())
         .await?;
     ctx.add_latests(latests.into_iter());
     ctx.add_history(history.into_iter());
+    if !availability_filters.is_empty() {
+        let availabilities = availability::Command::new(&availability_filters, params.window())
+            .execute(database.as_ref())
+            .await?;
+        ctx.add_availabilities(availabilities.into_iter());
+    }
+    for rollup in dashboard.collect_rollups() {
+        let filters = [MetricFilter::from(&rollup.header)];
+        let history = aggr::list::Command::new(&filters, params.window(), 30)
+            .with_group_by(rollup.group_by.clone())
+            .with_rate(rollup.rate)
+            .execute(database.as_ref())
+            .await?;
+        ctx.add_rollup(rollup, history.into_iter());
+    }
 
     let page = dashboard.build_view(ctx).await.unwrap();
 
@@ -1,11 +1,13 @@
 use axum::routing::get;
 use tower_http::compression::CompressionLayer;
 
+mod alerts;
 mod error;
 mod home;
 
 pub(super) fn create() -> axum::Router {
     axum::Router::new()
         .route("/", get(home::handle))
+        .route("/alerts", get(alerts::handle))
         .layer(CompressionLayer::new())
 }
@@ -0,0 +1,80 @@
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::Context;
+use tokio::io::AsyncWriteExt;
+
+use super::Notification;
+
+fn default_timeout() -> u64 {
+    30
+}
+
+/// Executes a command for each notification.
+///
+/// The notification is written as JSON on the standard input and exposed through
+/// the `CHEZMOI_ALERT_*` environment variables.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    program: String,
+    #[serde(default)]
+    args: Vec<String>,
+    /// Duration in seconds after which the command gets killed.
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+}
+
+impl Config {
+    pub fn build(&self) -> Channel {
+        Channel {
+            program: self.program.clone(),
+            args: self.args.clone(),
+            timeout: Duration::from_secs(self.timeout),
+        }
+    }
+}
+
+pub(crate) struct Channel {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl Channel {
+    async fn execute(&self, notification: &Notification) -> anyhow::Result<()> {
+        let mut child = tokio::process::Command::new(self.program.as_str())
+            .args(self.args.iter())
+            .env("CHEZMOI_ALERT_RULE", notification.rule.as_ref())
+            .env("CHEZMOI_ALERT_STATUS", notification.status.as_str())
+            .env("CHEZMOI_ALERT_METRIC", notification.header.name.as_ref())
+            .env(
+                "CHEZMOI_ALERT_VALUE",
+                notification
+                    .value
+                    .map(|v| v.to_string())
+                    .unwrap_or_default(),
+            )
+            .env("CHEZMOI_ALERT_SINCE", notification.since.to_string())
+            .env("CHEZMOI_ALERT_TEXT", notification.text())
+            .stdin(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("spawning {:?}", self.program))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let payload = serde_json::to_vec(notification)?;
+            // the command is free not to read its input
+            let _ = stdin.write_all(&payload).await;
+        }
+        let status = child.wait().await?;
+        if !status.success() {
+            anyhow::bail!("command exited with {status}");
+        }
+        Ok(())
+    }
+
+    pub async fn send(&self, notification: &Notification) -> anyhow::Result<()> {
+        tokio::time::timeout(self.timeout, self.execute(notification))
+            .await
+            .context("command timed out")?
+    }
+}
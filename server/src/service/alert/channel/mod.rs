@@ -0,0 +1,39 @@
+use super::Notification;
+
+pub(crate) mod command;
+pub(crate) mod smtp;
+pub(crate) mod webhook;
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub(crate) enum Config {
+    Webhook(webhook::Config),
+    Smtp(smtp::Config),
+    Command(command::Config),
+}
+
+impl Config {
+    pub fn build(&self) -> anyhow::Result<Channel> {
+        Ok(match self {
+            Self::Webhook(inner) => Channel::Webhook(inner.build()?),
+            Self::Smtp(inner) => Channel::Smtp(inner.build()?),
+            Self::Command(inner) => Channel::Command(inner.build()),
+        })
+    }
+}
+
+pub(crate) enum Channel {
+    Webhook(webhook::Channel),
+    Smtp(smtp::Channel),
+    Command(command::Channel),
+}
+
+impl Channel {
+    pub async fn send(&self, notification: &Notification) -> anyhow::Result<()> {
+        match self {
+            Self::Webhook(inner) => inner.send(notification).await,
+            Self::Smtp(inner) => inner.send(notification).await,
+            Self::Command(inner) => inner.send(notification).await,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::Context;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use super::Notification;
+
+#[derive(Clone, Copy, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Security {
+    /// Plain connection, only for local relays.
+    None,
+    #[default]
+    StartTls,
+    Tls,
+}
+
+/// Sends the notification by email.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    host: String,
+    /// Defaults to the standard port of the security mode.
+    #[serde(default)]
+    port: Option<u16>,
+    #[serde(default)]
+    security: Security,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    from: String,
+    to: Vec<String>,
+}
+
+impl Config {
+    pub fn build(&self) -> anyhow::Result<Channel> {
+        let mut builder = match self.security {
+            Security::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(self.host.as_str())
+            }
+            Security::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(self.host.as_str())
+                    .context("building smtp transport")?
+            }
+            Security::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(self.host.as_str())
+                .context("building smtp transport")?,
+        };
+        if let Some(port) = self.port {
+            builder = builder.port(port);
+        }
+        if let (Some(username), Some(password)) = (&self.username, &self.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        let from = self
+            .from
+            .parse()
+            .with_context(|| format!("invalid sender {:?}", self.from))?;
+        let to = self
+            .to
+            .iter()
+            .map(|item| {
+                item.parse()
+                    .with_context(|| format!("invalid recipient {item:?}"))
+            })
+            .collect::<anyhow::Result<Vec<Mailbox>>>()?;
+        if to.is_empty() {
+            anyhow::bail!("no recipient defined");
+        }
+        Ok(Channel {
+            transport: builder.build(),
+            from,
+            to,
+        })
+    }
+}
+
+pub(crate) struct Channel {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl Channel {
+    pub async fn send(&self, notification: &Notification) -> anyhow::Result<()> {
+        let builder = self
+            .to
+            .iter()
+            .cloned()
+            .fold(Message::builder().from(self.from.clone()), |builder, to| {
+                builder.to(to)
+            });
+        let message = builder
+            .subject(notification.subject())
+            .header(ContentType::TEXT_PLAIN)
+            .body(notification.text())?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
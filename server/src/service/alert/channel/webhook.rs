@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Context;
+
+use super::Notification;
+
+fn default_timeout() -> u64 {
+    10
+}
+
+/// Posts the notification as JSON to the given url.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    url: String,
+    /// Extra headers, like an authorization token.
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    /// Request timeout in seconds.
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+}
+
+impl Config {
+    pub fn build(&self) -> anyhow::Result<Channel> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in self.headers.iter() {
+            headers.insert(
+                reqwest::header::HeaderName::try_from(name.as_str())
+                    .with_context(|| format!("invalid header name {name:?}"))?,
+                reqwest::header::HeaderValue::try_from(value.as_str())
+                    .with_context(|| format!("invalid header value for {name:?}"))?,
+            );
+        }
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(self.timeout))
+            .default_headers(headers)
+            .build()
+            .context("building http client")?;
+        Ok(Channel {
+            client,
+            url: self.url.clone(),
+        })
+    }
+}
+
+pub(crate) struct Channel {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl Channel {
+    pub async fn send(&self, notification: &Notification) -> anyhow::Result<()> {
+        self.client
+            .post(self.url.as_str())
+            .json(notification)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
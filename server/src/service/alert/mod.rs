@@ -0,0 +1,493 @@
+//! Alerting engine, evaluating rules over the latest metrics and notifying channels
+//! when an alert starts firing or gets resolved.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use chezmoi_database::alert::{AlertStatus, AlertTransition};
+use chezmoi_database::helper::now;
+use chezmoi_database::metrics::entity::find_latest;
+use chezmoi_database::metrics::filter::MetricFilter;
+use chezmoi_database::metrics::MetricHeader;
+
+use crate::service::dashboard::Dashboard;
+
+pub(crate) mod channel;
+pub(crate) mod rule;
+pub(crate) mod view;
+
+fn default_interval() -> u64 {
+    60
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Config {
+    /// Interval in seconds between two evaluations of the rules.
+    #[serde(default = "default_interval")]
+    interval: u64,
+    #[serde(default)]
+    channels: HashMap<String, channel::Config>,
+    #[serde(default)]
+    rules: Vec<rule::Rule>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: default_interval(),
+            channels: Default::default(),
+            rules: Default::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Builds the engine with the configured rules, completed with the ranges
+    /// defined on the dashboard cards.
+    pub fn build(self, dashboard: &Dashboard) -> anyhow::Result<Engine> {
+        let mut names = HashSet::with_capacity(self.rules.len());
+        for rule in self.rules.iter() {
+            if !names.insert(rule.name.clone()) {
+                anyhow::bail!("rule {:?} is defined multiple times", rule.name);
+            }
+            if let Some(missing) = rule
+                .channels
+                .iter()
+                .find(|name| !self.channels.contains_key(name.as_str()))
+            {
+                anyhow::bail!("rule {:?} refers to unknown channel {missing:?}", rule.name);
+            }
+        }
+        let channels = self
+            .channels
+            .iter()
+            .map(|(name, config)| {
+                config
+                    .build()
+                    .map(|channel| (name.clone(), channel))
+                    .with_context(|| format!("building channel {name:?}"))
+            })
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+        let mut rules = self.rules;
+        rules.extend(
+            dashboard
+                .collect_alert_rules()
+                .into_iter()
+                .filter(|rule| names.insert(rule.name.clone())),
+        );
+        Ok(Engine {
+            interval: Duration::from_secs(self.interval.max(1)),
+            channels,
+            rules: Arc::new(rules),
+            states: Default::default(),
+        })
+    }
+}
+
+/// Message sent to the channels when an alert changes state.
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct Notification {
+    pub rule: Cow<'static, str>,
+    pub status: AlertStatus,
+    pub condition: String,
+    #[serde(flatten)]
+    pub header: MetricHeader,
+    pub value: Option<f64>,
+    /// When the condition started to be met.
+    pub since: u64,
+    pub timestamp: u64,
+}
+
+impl Notification {
+    pub fn subject(&self) -> String {
+        format!("[{}] {}", self.status.as_str().to_uppercase(), self.rule)
+    }
+
+    fn to_transition(&self) -> AlertTransition {
+        AlertTransition {
+            rule: self.rule.clone(),
+            header: self.header.clone(),
+            status: self.status,
+            value: self.value,
+            since: self.since,
+            timestamp: self.timestamp,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = format!("{} {}", self.header.name, self.condition);
+        for (key, value) in self.header.tags.entries() {
+            text.push_str(&format!(", {key}={}", serde_json::json!(value)));
+        }
+        if let Some(value) = self.value {
+            text.push_str(&format!(", value {value}"));
+        }
+        text
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    /// The condition is met but for less than the hold duration.
+    Pending {
+        since: u64,
+    },
+    Firing {
+        since: u64,
+    },
+}
+
+pub(crate) struct Engine {
+    interval: Duration,
+    channels: HashMap<String, channel::Channel>,
+    rules: Arc<Vec<rule::Rule>>,
+    /// State of the alerts, by rule index and serie, missing when inactive.
+    states: HashMap<(usize, MetricHeader), State>,
+}
+
+impl Engine {
+    pub fn rules(&self) -> Arc<Vec<rule::Rule>> {
+        self.rules.clone()
+    }
+
+    /// Restores the firing alerts from the persisted transitions, to avoid notifying
+    /// them again after a restart.
+    async fn restore(&mut self, database: &chezmoi_database::Client) -> anyhow::Result<()> {
+        let transitions = chezmoi_database::alert::find_latest::Command
+            .execute(database.as_ref())
+            .await?;
+        for transition in transitions {
+            if transition.status != AlertStatus::Firing {
+                continue;
+            }
+            if let Some(index) = self
+                .rules
+                .iter()
+                .position(|rule| rule.name == transition.rule)
+            {
+                self.states.insert(
+                    (index, transition.header),
+                    State::Firing {
+                        since: transition.since,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn evaluate(
+        &mut self,
+        database: &chezmoi_database::Client,
+        now: u64,
+    ) -> anyhow::Result<Vec<(usize, Notification)>> {
+        let mut notifications = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let header = rule.header();
+            let metrics = find_latest::Command::new(&[MetricFilter::from(header)], (0, now), None)
+                .execute(database.as_ref())
+                .await?;
+            let mut observations = rule.observe(now, metrics);
+            // series that are not reported anymore are considered as not matching
+            observations.extend(
+                self.states
+                    .keys()
+                    .filter(|(idx, header)| {
+                        *idx == index && !observations.iter().any(|obs| obs.header.eq(header))
+                    })
+                    .map(|(_, header)| rule::Observation {
+                        header: header.clone(),
+                        matching: false,
+                        value: None,
+                    })
+                    .collect::<Vec<_>>(),
+            );
+
+            for observation in observations {
+                let key = (index, observation.header);
+                let status = match (observation.matching, self.states.get(&key)) {
+                    (true, None) if rule.hold == 0 => {
+                        self.states
+                            .insert(key.clone(), State::Firing { since: now });
+                        Some((AlertStatus::Firing, now))
+                    }
+                    (true, None) => {
+                        self.states
+                            .insert(key.clone(), State::Pending { since: now });
+                        None
+                    }
+                    (true, Some(State::Pending { since })) if *since + rule.hold <= now => {
+                        let since = *since;
+                        self.states.insert(key.clone(), State::Firing { since });
+                        Some((AlertStatus::Firing, since))
+                    }
+                    (true, Some(_)) => None,
+                    (false, Some(State::Firing { since })) => {
+                        let since = *since;
+                        self.states.remove(&key);
+                        Some((AlertStatus::Resolved, since))
+                    }
+                    (false, Some(State::Pending { .. })) => {
+                        self.states.remove(&key);
+                        None
+                    }
+                    (false, None) => None,
+                };
+                if let Some((status, since)) = status {
+                    notifications.push((
+                        index,
+                        Notification {
+                            rule: rule.name.clone(),
+                            status,
+                            condition: rule.condition.to_string(),
+                            header: key.1,
+                            value: observation.value,
+                            since,
+                            timestamp: now,
+                        },
+                    ));
+                }
+            }
+        }
+        Ok(notifications)
+    }
+
+    async fn notify(
+        &self,
+        database: &chezmoi_database::Client,
+        index: usize,
+        notification: &Notification,
+    ) {
+        tracing::info!(
+            message = "alert changed state",
+            rule = %notification.rule,
+            status = notification.status.as_str(),
+            metric = %notification.header.name,
+        );
+        if let Err(err) =
+            chezmoi_database::alert::create::Command::new(&notification.to_transition())
+                .execute(database.as_ref())
+                .await
+        {
+            tracing::error!(message = "unable to persist alert transition", cause = %err);
+        }
+        for name in self.rules[index].channels.iter() {
+            let Some(channel) = self.channels.get(name) else {
+                continue;
+            };
+            if let Err(err) = channel.send(notification).await {
+                tracing::error!(
+                    message = "unable to send notification",
+                    channel = %name,
+                    cause = %err
+                );
+            }
+        }
+    }
+
+    pub async fn run(mut self, database: chezmoi_database::Client) {
+        if self.rules.is_empty() {
+            tracing::debug!("no alerting rule defined");
+            return;
+        }
+        if let Err(err) = self.restore(&database).await {
+            tracing::error!(message = "unable to restore alerts state", cause = %err);
+        }
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            match self.evaluate(&database, now()).await {
+                Ok(notifications) => {
+                    for (index, notification) in notifications.iter() {
+                        self.notify(&database, *index, notification).await;
+                    }
+                }
+                Err(err) => {
+                    tracing::error!(message = "unable to evaluate alerting rules", cause = %err)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use chezmoi_database::alert::AlertStatus;
+    use chezmoi_database::metrics::entity::{create, Metric, MetricValue};
+    use chezmoi_database::metrics::MetricHeader;
+
+    use super::rule::{Condition, Rule};
+
+    async fn database() -> chezmoi_database::Client {
+        let client = chezmoi_database::Config::memory().build().await.unwrap();
+        client.upgrade().await.unwrap();
+        client
+    }
+
+    fn engine(rule: Rule) -> super::Engine {
+        super::Engine {
+            interval: Duration::from_secs(60),
+            channels: HashMap::new(),
+            rules: Arc::new(vec![rule]),
+            states: HashMap::new(),
+        }
+    }
+
+    fn header() -> MetricHeader {
+        MetricHeader::new("miflora.moisture").with_tag("address", "C4:7C:8D:6A:3E:1F")
+    }
+
+    async fn insert(database: &chezmoi_database::Client, timestamp: u64, value: f64) {
+        create::Command::new(&[Metric {
+            timestamp,
+            header: header(),
+            value: MetricValue::gauge(value),
+        }])
+        .execute(database.as_ref())
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_fire_once_hold_expired() {
+        let database = database().await;
+        let mut rule = Rule::new(
+            "ficus-needs-water",
+            header(),
+            Condition::Below { threshold: 20.0 },
+        );
+        rule.hold = 60;
+        let mut engine = engine(rule);
+
+        insert(&database, 100, 12.5).await;
+        assert!(engine.evaluate(&database, 100).await.unwrap().is_empty());
+        assert!(engine.evaluate(&database, 130).await.unwrap().is_empty());
+
+        let notifications = engine.evaluate(&database, 160).await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        let (index, notification) = &notifications[0];
+        assert_eq!(*index, 0);
+        assert_eq!(notification.status, AlertStatus::Firing);
+        assert_eq!(notification.since, 100);
+        assert_eq!(notification.timestamp, 160);
+        assert_eq!(notification.value, Some(12.5));
+        assert_eq!(notification.header, header());
+
+        // only notified once
+        assert!(engine.evaluate(&database, 190).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_resolve_after_firing() {
+        let database = database().await;
+        let mut engine = engine(Rule::new(
+            "ficus-needs-water",
+            header(),
+            Condition::Below { threshold: 20.0 },
+        ));
+
+        insert(&database, 100, 12.5).await;
+        let notifications = engine.evaluate(&database, 100).await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].1.status, AlertStatus::Firing);
+
+        insert(&database, 110, 25.0).await;
+        let notifications = engine.evaluate(&database, 110).await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        let (_, notification) = &notifications[0];
+        assert_eq!(notification.status, AlertStatus::Resolved);
+        assert_eq!(notification.since, 100);
+        assert_eq!(notification.timestamp, 110);
+        assert_eq!(notification.value, Some(25.0));
+
+        assert!(engine.evaluate(&database, 120).await.unwrap().is_empty());
+        assert!(engine.states.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_drop_pending_on_recovery() {
+        let database = database().await;
+        let mut rule = Rule::new(
+            "ficus-needs-water",
+            header(),
+            Condition::Below { threshold: 20.0 },
+        );
+        rule.hold = 60;
+        let mut engine = engine(rule);
+
+        insert(&database, 100, 12.5).await;
+        assert!(engine.evaluate(&database, 100).await.unwrap().is_empty());
+        assert_eq!(engine.states.len(), 1);
+
+        // recovering before the hold expires doesn't notify anything
+        insert(&database, 130, 25.0).await;
+        assert!(engine.evaluate(&database, 130).await.unwrap().is_empty());
+        assert!(engine.states.is_empty());
+
+        // the hold starts over
+        insert(&database, 140, 12.5).await;
+        assert!(engine.evaluate(&database, 140).await.unwrap().is_empty());
+        assert!(engine.evaluate(&database, 170).await.unwrap().is_empty());
+        let notifications = engine.evaluate(&database, 200).await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].1.status, AlertStatus::Firing);
+        assert_eq!(notifications[0].1.since, 140);
+    }
+
+    #[tokio::test]
+    async fn should_fire_when_absent_without_serie() {
+        let database = database().await;
+        let mut engine = engine(Rule::new(
+            "ficus-silent",
+            header(),
+            Condition::Absent { duration: 60 },
+        ));
+
+        let notifications = engine.evaluate(&database, 100).await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        let (_, notification) = &notifications[0];
+        assert_eq!(notification.status, AlertStatus::Firing);
+        assert_eq!(notification.header, header());
+        assert!(notification.value.is_none());
+
+        // the serie shows up
+        insert(&database, 110, 12.5).await;
+        let notifications = engine.evaluate(&database, 110).await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].1.status, AlertStatus::Resolved);
+        assert_eq!(notifications[0].1.since, 100);
+    }
+
+    #[tokio::test]
+    async fn should_fire_when_serie_is_absent() {
+        let database = database().await;
+        let mut engine = engine(Rule::new(
+            "ficus-silent",
+            header(),
+            Condition::Absent { duration: 60 },
+        ));
+
+        insert(&database, 100, 12.5).await;
+        assert!(engine.evaluate(&database, 100).await.unwrap().is_empty());
+        assert!(engine.evaluate(&database, 160).await.unwrap().is_empty());
+
+        let notifications = engine.evaluate(&database, 161).await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        let (_, notification) = &notifications[0];
+        assert_eq!(notification.status, AlertStatus::Firing);
+        assert_eq!(notification.since, 161);
+        assert_eq!(notification.value, Some(12.5));
+
+        insert(&database, 170, 12.5).await;
+        let notifications = engine.evaluate(&database, 170).await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].1.status, AlertStatus::Resolved);
+    }
+}
@@ -0,0 +1,224 @@
+use std::borrow::Cow;
+
+use chezmoi_database::metrics::entity::{Metric, MetricValue};
+use chezmoi_database::metrics::{MetricHeader, MetricName, MetricTags};
+
+/// Condition that makes a rule fire.
+#[derive(Clone, Copy, Debug, serde::Deserialize)]
+#[serde(tag = "condition", rename_all = "kebab-case")]
+pub(crate) enum Condition {
+    /// The latest value is strictly above the threshold.
+    Above { threshold: f64 },
+    /// The latest value is strictly below the threshold.
+    Below { threshold: f64 },
+    /// No value has been received for `duration` seconds.
+    Absent { duration: u64 },
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Above { threshold } => write!(f, "above {threshold}"),
+            Self::Below { threshold } => write!(f, "below {threshold}"),
+            Self::Absent { duration } => write!(f, "absent for {duration}s"),
+        }
+    }
+}
+
+/// Result of a rule evaluated against a single serie.
+#[derive(Debug)]
+pub(crate) struct Observation {
+    pub header: MetricHeader,
+    pub matching: bool,
+    pub value: Option<f64>,
+}
+
+fn as_number(value: &MetricValue) -> f64 {
+    match value {
+        MetricValue::Count { value } => *value as f64,
+        MetricValue::Gauge { value } => *value,
+        MetricValue::Bool { value } => {
+            if *value {
+                1.0
+            } else {
+                0.0
+            }
+        }
+    }
+}
+
+/// An alerting rule, matching the series with the given name and tags.
+///
+/// ```toml
+/// [[server.alerting.rules]]
+/// name = "ficus-needs-water"
+/// metric = "miflora.moisture"
+/// tags = { address = "C4:7C:8D:6A:3E:1F" }
+/// condition = "below"
+/// threshold = 20.0
+/// hold = 600
+/// channels = ["phone"]
+/// ```
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Rule {
+    pub name: Cow<'static, str>,
+    metric: MetricName,
+    #[serde(default)]
+    tags: MetricTags,
+    #[serde(flatten)]
+    pub condition: Condition,
+    /// Duration in seconds the condition should be met before firing.
+    #[serde(default)]
+    pub hold: u64,
+    /// Names of the channels being notified.
+    #[serde(default)]
+    pub channels: Vec<String>,
+}
+
+impl Rule {
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        header: MetricHeader,
+        condition: Condition,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            metric: header.name,
+            tags: header.tags,
+            condition,
+            hold: 0,
+            channels: Vec::new(),
+        }
+    }
+
+    pub fn header(&self) -> MetricHeader {
+        MetricHeader {
+            name: self.metric.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+
+    /// Evaluates the condition against the latest value of each matching serie.
+    ///
+    /// When nothing matches the rule, the rule header is reported so that an absent
+    /// condition can fire.
+    pub fn observe(&self, now: u64, metrics: Vec<Metric>) -> Vec<Observation> {
+        if metrics.is_empty() {
+            return vec![Observation {
+                header: self.header(),
+                matching: matches!(self.condition, Condition::Absent { .. }),
+                value: None,
+            }];
+        }
+        metrics
+            .into_iter()
+            .map(|metric| {
+                let value = as_number(&metric.value);
+                let matching = match self.condition {
+                    Condition::Above { threshold } => value > threshold,
+                    Condition::Below { threshold } => value < threshold,
+                    Condition::Absent { duration } => {
+                        metric.timestamp.saturating_add(duration) < now
+                    }
+                };
+                Observation {
+                    header: metric.header,
+                    matching,
+                    value: Some(value),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chezmoi_database::metrics::entity::{Metric, MetricValue};
+    use chezmoi_database::metrics::MetricHeader;
+
+    use super::{Condition, Rule};
+
+    fn metric(timestamp: u64, address: &'static str, value: MetricValue) -> Metric {
+        Metric {
+            timestamp,
+            header: MetricHeader::new("miflora.moisture").with_tag("address", address),
+            value,
+        }
+    }
+
+    #[test]
+    fn should_compare_with_threshold() {
+        let rule = Rule::new(
+            "ficus-needs-water",
+            MetricHeader::new("miflora.moisture"),
+            Condition::Below { threshold: 20.0 },
+        );
+        let observations = rule.observe(
+            100,
+            vec![
+                metric(90, "first", MetricValue::gauge(12.5)),
+                metric(90, "second", MetricValue::gauge(20.0)),
+            ],
+        );
+        assert_eq!(observations.len(), 2);
+        assert!(observations[0].matching);
+        assert_eq!(observations[0].value, Some(12.5));
+        // the threshold is strict
+        assert!(!observations[1].matching);
+
+        let rule = Rule::new(
+            "ficus-drowning",
+            MetricHeader::new("miflora.moisture"),
+            Condition::Above { threshold: 0.0 },
+        );
+        let observations = rule.observe(100, vec![metric(90, "first", MetricValue::bool(true))]);
+        assert!(observations[0].matching);
+        assert_eq!(observations[0].value, Some(1.0));
+    }
+
+    #[test]
+    fn should_report_rule_header_without_series() {
+        let header = MetricHeader::new("miflora.moisture").with_tag("address", "first");
+        let rule = Rule::new(
+            "ficus-needs-water",
+            header.clone(),
+            Condition::Below { threshold: 20.0 },
+        );
+        let observations = rule.observe(100, Vec::new());
+        assert_eq!(observations.len(), 1);
+        assert_eq!(observations[0].header, header);
+        assert!(!observations[0].matching);
+        assert!(observations[0].value.is_none());
+
+        let rule = Rule::new(
+            "ficus-silent",
+            header.clone(),
+            Condition::Absent { duration: 60 },
+        );
+        let observations = rule.observe(100, Vec::new());
+        assert_eq!(observations.len(), 1);
+        assert_eq!(observations[0].header, header);
+        assert!(observations[0].matching);
+    }
+
+    #[test]
+    fn should_detect_absent_series() {
+        let rule = Rule::new(
+            "ficus-silent",
+            MetricHeader::new("miflora.moisture"),
+            Condition::Absent { duration: 60 },
+        );
+        let observations = rule.observe(
+            200,
+            vec![
+                metric(100, "first", MetricValue::gauge(12.5)),
+                metric(140, "second", MetricValue::gauge(12.5)),
+                metric(150, "third", MetricValue::gauge(12.5)),
+            ],
+        );
+        assert!(observations[0].matching);
+        // the duration is strict
+        assert!(!observations[1].matching);
+        assert!(!observations[2].matching);
+    }
+}
@@ -0,0 +1,68 @@
+use std::borrow::Cow;
+
+use chezmoi_client::view::alerts;
+use chezmoi_database::alert::{AlertStatus, AlertTransition};
+use chezmoi_database::metrics::MetricHeader;
+
+use super::rule::Rule;
+
+/// Maximum number of transitions displayed for each alert.
+const MAX_TRANSITIONS: usize = 10;
+
+fn status(value: AlertStatus) -> alerts::Status {
+    match value {
+        AlertStatus::Firing => alerts::Status::Firing,
+        AlertStatus::Resolved => alerts::Status::Resolved,
+    }
+}
+
+fn target(header: &MetricHeader) -> String {
+    header
+        .tags
+        .entries()
+        .fold(header.name.to_string(), |mut res, (key, value)| {
+            res.push_str(&format!(" {key}={}", serde_json::json!(value)));
+            res
+        })
+}
+
+/// Builds the alerts view, showing the firing alerts and the ones resolved after `since`.
+pub(crate) fn build_view<'a>(
+    rules: &'a [Rule],
+    latest: Vec<AlertTransition>,
+    history: Vec<AlertTransition>,
+    since: u64,
+) -> alerts::View<'a> {
+    let rules = rules
+        .iter()
+        .map(|rule| {
+            let alerts = latest
+                .iter()
+                .filter(|item| item.rule == rule.name)
+                .filter(|item| item.status == AlertStatus::Firing || item.timestamp >= since)
+                .map(|item| alerts::Alert {
+                    target: Cow::Owned(target(&item.header)),
+                    status: status(item.status),
+                    since: item.since,
+                    value: item.value,
+                    transitions: history
+                        .iter()
+                        .filter(|entry| entry.rule == item.rule && entry.header == item.header)
+                        .take(MAX_TRANSITIONS)
+                        .map(|entry| alerts::Transition {
+                            status: status(entry.status),
+                            value: entry.value,
+                            timestamp: entry.timestamp,
+                        })
+                        .collect(),
+                })
+                .collect();
+            alerts::Rule {
+                name: Cow::Borrowed(rule.name.as_ref()),
+                condition: Cow::Owned(rule.condition.to_string()),
+                alerts,
+            }
+        })
+        .collect();
+    alerts::View::new(rules)
+}
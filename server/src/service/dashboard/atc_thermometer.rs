@@ -43,7 +43,7 @@ impl AtcThermometerCard {
         buffer.insert(header(DEVICE_HUMIDITY, self.address.clone()));
     }
 
-    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard, String> {
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
         let temperature = find_gauge(DEVICE_TEMPERATURE, self.address.clone(), ctx);
         let humidity = find_gauge(DEVICE_HUMIDITY, self.address.clone(), ctx);
         let battery = find_gauge(DEVICE_BATTERY, self.address.clone(), ctx);
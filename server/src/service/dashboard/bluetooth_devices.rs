@@ -0,0 +1,108 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chezmoi_agent::sensor::bt_scanner::{DEVICE_BATTERY, DEVICE_POWER};
+use chezmoi_client::component::card::bluetooth_devices::{Card, DeviceValues};
+use chezmoi_client::component::card::AnyCard as ClientAnyCard;
+use chezmoi_database::metrics::MetricHeader;
+
+use super::BuilderContext;
+
+/// The agent sends the unchanged values again after an hour, the default
+/// window leaves some room for a device that stayed still.
+fn default_window() -> u64 {
+    60 * 60 * 2
+}
+
+fn tag_text<'a>(header: &'a MetricHeader, name: &str) -> Option<&'a str> {
+    header.tags.get(name).and_then(|value| value.as_text())
+}
+
+/// The removal of a device is recorded with a null power and only its address.
+fn is_removal(header: &MetricHeader, power: f64) -> bool {
+    power == 0.0 && header.tags.entries().count() == 1
+}
+
+#[derive(Debug)]
+struct LatestPower<'a> {
+    timestamp: u64,
+    header: &'a MetricHeader,
+    power: f64,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct BluetoothDevicesCard {
+    /// Devices not seen for this number of seconds are hidden.
+    #[serde(default = "default_window")]
+    window: u64,
+}
+
+impl From<BluetoothDevicesCard> for super::AnyCard {
+    fn from(value: BluetoothDevicesCard) -> Self {
+        Self::BluetoothDevices(value)
+    }
+}
+
+impl BluetoothDevicesCard {
+    /// Without tags, all the devices are collected.
+    pub fn collect_latest_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        buffer.insert(MetricHeader::new(DEVICE_POWER));
+        buffer.insert(MetricHeader::new(DEVICE_BATTERY));
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        let since = ctx.window.1.saturating_sub(self.window);
+
+        // a device can have several series when its name shows up later
+        let mut powers: BTreeMap<&str, LatestPower<'_>> = BTreeMap::new();
+        let mut batteries: BTreeMap<&str, (u64, f64)> = BTreeMap::new();
+        for (header, (timestamp, value)) in ctx.latest.iter() {
+            let (Some(address), Some(value)) = (tag_text(header, "address"), value.as_gauge())
+            else {
+                continue;
+            };
+            match header.name.as_ref() {
+                DEVICE_POWER
+                    if powers
+                        .get(address)
+                        .is_none_or(|previous| previous.timestamp < *timestamp) =>
+                {
+                    powers.insert(
+                        address,
+                        LatestPower {
+                            timestamp: *timestamp,
+                            header,
+                            power: value,
+                        },
+                    );
+                }
+                DEVICE_BATTERY
+                    if batteries
+                        .get(address)
+                        .is_none_or(|(previous, _)| previous < timestamp) =>
+                {
+                    batteries.insert(address, (*timestamp, value));
+                }
+                _ => {}
+            }
+        }
+
+        let devices = powers
+            .into_iter()
+            .filter(|(_, latest)| latest.timestamp >= since)
+            .filter(|(_, latest)| !is_removal(latest.header, latest.power))
+            .map(|(address, latest)| {
+                let battery = batteries.get(address).filter(|(ts, _)| *ts >= since);
+                DeviceValues {
+                    address: address.to_string().into(),
+                    name: tag_text(latest.header, "name").map(|name| name.to_string().into()),
+                    tx_power: latest.power,
+                    battery: battery.map(|(_, value)| *value),
+                    timestamp: battery
+                        .map_or(latest.timestamp, |(ts, _)| latest.timestamp.max(*ts)),
+                }
+            })
+            .collect();
+
+        Ok(ClientAnyCard::BluetoothDevices(Card::new(devices)))
+    }
+}
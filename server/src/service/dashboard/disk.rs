@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chezmoi_agent::sensor::disk::{
+    DISK_RATIO, DISK_READ_RATE, DISK_TOTAL, DISK_USED, DISK_WRITE_RATE,
+};
+use chezmoi_client::component::card::disk::Card as ClientDiskCard;
+use chezmoi_client::component::card::history_chart::Card as ClientHistoryChardCard;
+use chezmoi_client::component::card::AnyCard as ClientAnyCard;
+use chezmoi_client::component::line_chart::Serie;
+use chezmoi_client::Dimension;
+use chezmoi_database::metrics::MetricHeader;
+
+use super::{BuilderContext, Size, Statistics};
+
+fn default_mount() -> String {
+    String::from("/")
+}
+
+fn tag_text<'a>(header: &'a MetricHeader, name: &str) -> Option<&'a str> {
+    header.tags.get(name).and_then(|value| value.as_text())
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct DiskCard {
+    #[serde(default = "default_mount")]
+    mount: String,
+}
+
+impl From<DiskCard> for super::AnyCard {
+    fn from(value: DiskCard) -> Self {
+        Self::Disk(value)
+    }
+}
+
+impl DiskCard {
+    fn header(&self, name: &'static str) -> MetricHeader {
+        MetricHeader::new(name).with_tag("mount", self.mount.clone())
+    }
+
+    pub fn collect_latest_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        buffer.insert(self.header(DISK_TOTAL));
+        buffer.insert(self.header(DISK_USED));
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        let find_gauge = |name| {
+            ctx.latest
+                .get(&self.header(name))
+                .and_then(|(_, value)| value.as_gauge())
+        };
+        Ok(ClientAnyCard::Disk(ClientDiskCard::new(
+            self.mount.as_str(),
+            find_gauge(DISK_TOTAL),
+            find_gauge(DISK_USED),
+        )))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum DiskHistoryKind {
+    /// Used space of each mount point, in percent.
+    #[default]
+    Usage,
+    /// Read and write rates of each device.
+    Io,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct DiskHistoryCard {
+    #[serde(default)]
+    kind: DiskHistoryKind,
+    /// Mount points, or devices, to display, all of them when empty.
+    #[serde(default)]
+    filter: Vec<String>,
+    #[serde(default)]
+    statistics: Statistics,
+    #[serde(default = "Size::sm")]
+    height: Size,
+    #[serde(default = "Size::md")]
+    width: Size,
+}
+
+impl From<DiskHistoryCard> for super::AnyCard {
+    fn from(value: DiskHistoryCard) -> Self {
+        Self::DiskHistory(value)
+    }
+}
+
+impl DiskHistoryCard {
+    fn accept(&self, value: &str) -> bool {
+        self.filter.is_empty() || self.filter.iter().any(|item| item == value)
+    }
+
+    /// Name of the serie for the given metric, if it should be displayed.
+    fn serie_name(&self, header: &MetricHeader) -> Option<String> {
+        match (self.kind, header.name.as_ref()) {
+            (DiskHistoryKind::Usage, DISK_RATIO) => tag_text(header, "mount")
+                .filter(|mount| self.accept(mount))
+                .map(String::from),
+            (DiskHistoryKind::Io, DISK_READ_RATE) => tag_text(header, "device")
+                .filter(|device| self.accept(device))
+                .map(|device| format!("{device} read")),
+            (DiskHistoryKind::Io, DISK_WRITE_RATE) => tag_text(header, "device")
+                .filter(|device| self.accept(device))
+                .map(|device| format!("{device} write")),
+            _ => None,
+        }
+    }
+
+    pub fn collect_history_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        match self.kind {
+            DiskHistoryKind::Usage => {
+                buffer.insert(MetricHeader::new(DISK_RATIO));
+            }
+            DiskHistoryKind::Io => {
+                buffer.insert(MetricHeader::new(DISK_READ_RATE));
+                buffer.insert(MetricHeader::new(DISK_WRITE_RATE));
+            }
+        }
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        // sorted by name to keep the same colors between renderings
+        let series: BTreeMap<String, Vec<(u64, f64)>> = ctx
+            .history
+            .iter()
+            .filter_map(|(header, list)| {
+                let name = self.serie_name(header)?;
+                Some(self.statistics.gauge_series(&name, list))
+            })
+            .flatten()
+            .collect();
+
+        let (title, range) = match self.kind {
+            DiskHistoryKind::Usage => ("Disk usage", Some(0.0..100.0)),
+            DiskHistoryKind::Io => ("Disk I/O", None),
+        };
+        Ok(ClientAnyCard::HistoryChart(ClientHistoryChardCard::new(
+            title,
+            Dimension::new(self.width.into(), self.height.into()),
+            series
+                .into_iter()
+                .map(|(name, values)| Serie::new(name, values))
+                .collect(),
+            Some(ctx.window.0..ctx.window.1),
+            range,
+        )))
+    }
+}
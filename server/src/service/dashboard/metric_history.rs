@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+
+use chezmoi_client::component::card::history_chart::Card as ClientHistoryChardCard;
+use chezmoi_client::component::card::AnyCard as ClientAnyCard;
+use chezmoi_client::component::line_chart::Serie;
+use chezmoi_client::Dimension;
+use chezmoi_database::metrics::aggr::list::GroupBy;
+use chezmoi_database::metrics::aggr::MetricValueAggr;
+use chezmoi_database::metrics::{MetricHeader, MetricTags};
+
+use super::{BuilderContext, Rollup, Size, Statistics};
+
+/// Name of a serie, based on its remaining tags, the keys avoiding two series with the
+/// same values, like `room=kitchen` and `floor=kitchen`, to get the same name.
+fn serie_name(header: &MetricHeader, fallback: &str) -> String {
+    let values = header
+        .tags
+        .entries()
+        .map(|(key, value)| match value.as_text() {
+            Some(text) => format!("{key}={text}"),
+            None => format!("{key}={}", serde_json::to_string(value).unwrap_or_default()),
+        })
+        .collect::<Vec<_>>();
+    if values.is_empty() {
+        fallback.to_string()
+    } else {
+        values.join(" ")
+    }
+}
+
+/// Counts are displayed with their total, or their rate, over each division, in a single serie
+/// whatever the statistics.
+fn count_value(value: &MetricValueAggr) -> Option<f64> {
+    match value {
+        MetricValueAggr::Count(inner) => Some(inner.sum as f64),
+        MetricValueAggr::Gauge(_) => None,
+        MetricValueAggr::Rate(inner) => Some(inner.rate),
+    }
+}
+
+/// History of any metric, the series being optionally merged over their tags.
+///
+/// ```toml
+/// [[server.dashboard.sections.cards]]
+/// type = "metric-history"
+/// title = "Temperature upstairs"
+/// name = "atc-thermometer.temperature"
+/// tags = { room = "upstairs" }
+/// group_by = []
+/// statistics = ["avg", "max"]
+/// ```
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct MetricHistoryCard {
+    title: String,
+    name: String,
+    /// Only the series with those tags are displayed.
+    #[serde(default)]
+    tags: MetricTags,
+    /// Tags to keep when merging the series, all the series being merged when empty.
+    /// Without it, every serie is displayed on its own.
+    #[serde(default)]
+    group_by: Option<Vec<String>>,
+    /// Displays the count metrics, like restarts or received bytes, as per second rates.
+    #[serde(default)]
+    rate: bool,
+    /// Statistics plotted for the gauges.
+    #[serde(default)]
+    statistics: Statistics,
+    #[serde(default = "Size::sm")]
+    height: Size,
+    #[serde(default = "Size::md")]
+    width: Size,
+}
+
+impl From<MetricHistoryCard> for super::AnyCard {
+    fn from(value: MetricHistoryCard) -> Self {
+        Self::MetricHistory(value)
+    }
+}
+
+impl MetricHistoryCard {
+    fn rollup(&self) -> Rollup {
+        Rollup {
+            header: MetricHeader::from((self.name.clone(), self.tags.clone())),
+            group_by: match self.group_by {
+                Some(ref tags) => GroupBy::Tags(tags.iter().cloned().map(Into::into).collect()),
+                None => GroupBy::Serie,
+            },
+            rate: self.rate,
+        }
+    }
+
+    pub fn collect_rollups(&self, buffer: &mut HashSet<Rollup>) {
+        buffer.insert(self.rollup());
+    }
+
+    fn series(&self, ctx: &BuilderContext) -> Vec<(String, Vec<(u64, f64)>)> {
+        let Some(history) = ctx.rollups.get(&self.rollup()) else {
+            return Vec::new();
+        };
+        let mut series = Vec::with_capacity(history.len() * self.statistics.iter().count());
+        for (header, list) in history.iter() {
+            let name = serie_name(header, &self.title);
+            if list.iter().any(|(_, value)| value.as_gauge().is_some()) {
+                series.extend(self.statistics.gauge_series(&name, list));
+            } else {
+                let values = list
+                    .iter()
+                    .filter_map(|(ts, value)| count_value(value).map(|v| (*ts, v)))
+                    .collect();
+                series.push((name, values));
+            }
+        }
+        // sorted by name to keep the same colors between renderings
+        series.sort_by(|(first, _), (second, _)| first.cmp(second));
+        series
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        let series = self.series(ctx);
+        Ok(ClientAnyCard::HistoryChart(ClientHistoryChardCard::new(
+            self.title.as_str(),
+            Dimension::new(self.width.into(), self.height.into()),
+            series
+                .into_iter()
+                .map(|(name, values)| Serie::new(name, values))
+                .collect(),
+            Some(ctx.window.0..ctx.window.1),
+            None,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chezmoi_client::view::dashboard::TimePickerDuration;
+    use chezmoi_database::metrics::aggr::{MetricCountAggr, MetricValueAggr};
+    use chezmoi_database::metrics::MetricHeader;
+
+    use crate::service::dashboard::BuilderContext;
+
+    fn count(sum: u64) -> MetricValueAggr {
+        MetricValueAggr::Count(MetricCountAggr {
+            min: 0,
+            avg: sum as f64,
+            max: sum,
+            sum,
+        })
+    }
+
+    #[test]
+    fn should_build_one_serie_per_count_without_collision() {
+        let card: super::MetricHistoryCard = toml::from_str(
+            r#"title = "Restarts"
+name = "service.restarts"
+statistics = ["avg", "max"]
+"#,
+        )
+        .unwrap();
+        let mut ctx = BuilderContext::new(TimePickerDuration::OneHour, (0, 60));
+        let mut history = std::collections::HashMap::new();
+        history.insert(
+            MetricHeader::new("service.restarts").with_tag("room", "kitchen"),
+            vec![(10, count(1))],
+        );
+        history.insert(
+            MetricHeader::new("service.restarts").with_tag("floor", "kitchen"),
+            vec![(10, count(2))],
+        );
+        ctx.rollups.insert(card.rollup(), history);
+
+        let series = card.series(&ctx);
+        assert_eq!(
+            series,
+            vec![
+                ("floor=kitchen".to_string(), vec![(10, 2.0)]),
+                ("room=kitchen".to_string(), vec![(10, 1.0)]),
+            ]
+        );
+    }
+}
@@ -6,6 +6,7 @@ use chezmoi_client::component::card::AnyCard as ClientAnyCard;
 use chezmoi_database::metrics::MetricHeader;
 
 use super::BuilderContext;
+use crate::service::alert::rule::{Condition, Rule};
 
 fn header(name: &'static str, address: Cow<'static, str>) -> MetricHeader {
     MetricHeader::new(name).with_tag("address", address)
@@ -33,6 +34,23 @@ impl Range {
     fn as_tuple(&self) -> (Option<f64>, Option<f64>) {
         (self.min, self.max)
     }
+
+    fn collect_alert_rules(&self, label: &str, header: MetricHeader, buffer: &mut Vec<Rule>) {
+        if let Some(threshold) = self.min {
+            buffer.push(Rule::new(
+                format!("{label} too low"),
+                header.clone(),
+                Condition::Below { threshold },
+            ));
+        }
+        if let Some(threshold) = self.max {
+            buffer.push(Rule::new(
+                format!("{label} too high"),
+                header,
+                Condition::Above { threshold },
+            ));
+        }
+    }
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -60,6 +78,23 @@ impl From<MifloraCard> for super::AnyCard {
 }
 
 impl MifloraCard {
+    pub fn collect_alert_rules(&self, buffer: &mut Vec<Rule>) {
+        let name = self.name.as_deref().unwrap_or(self.address.as_ref());
+        for (label, metric, range) in [
+            ("temperature", "miflora.temperature", &self.temperature),
+            ("brightness", "miflora.brightness", &self.brightness),
+            ("moisture", "miflora.moisture", &self.moisture),
+            ("conductivity", "miflora.conductivity", &self.conductivity),
+            ("battery", "miflora.battery", &self.battery),
+        ] {
+            range.collect_alert_rules(
+                &format!("{name} {label}"),
+                header(metric, self.address.clone()),
+                buffer,
+            );
+        }
+    }
+
     pub fn collect_latest_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
         buffer.insert(header("miflora.temperature", self.address.clone()));
         buffer.insert(header("miflora.brightness", self.address.clone()));
@@ -68,7 +103,7 @@ impl MifloraCard {
         buffer.insert(header("miflora.battery", self.address.clone()));
     }
 
-    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard, String> {
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
         Ok(ClientAnyCard::Miflora(Card::new(
             self.address.as_ref(),
             self.name.as_deref(),
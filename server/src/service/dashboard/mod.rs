@@ -1,17 +1,31 @@
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use chezmoi_client::component::card::AnyCard as ClientAnyCard;
 use chezmoi_client::view::dashboard::{self, TimePickerDuration};
-use chezmoi_database::metrics::aggr::{MetricAggr, MetricValueAggr};
+use chezmoi_database::metrics::aggr::list::GroupBy;
+use chezmoi_database::metrics::aggr::{MetricAggr, MetricValueAggr, Statistic};
+use chezmoi_database::metrics::entity::availability::Availability;
 use chezmoi_database::metrics::entity::{Metric, MetricValue};
 use chezmoi_database::metrics::MetricHeader;
 
+use crate::service::alert::rule::Rule;
+
 #[cfg(feature = "bluetooth")]
 pub(crate) mod atc_thermometer;
 #[cfg(feature = "bluetooth")]
+pub(crate) mod bluetooth_devices;
+pub(crate) mod disk;
+pub(crate) mod metric_history;
+#[cfg(feature = "bluetooth")]
 pub(crate) mod miflora;
+pub(crate) mod network;
+pub(crate) mod process;
+pub(crate) mod reachability;
 pub(crate) mod system;
+pub(crate) mod thermal;
+#[cfg(feature = "http-probe")]
+pub(crate) mod uptime;
 
 #[derive(Clone, Copy, Debug, serde::Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -30,6 +44,121 @@ impl Size {
     }
 }
 
+/// Statistics of the aggregated gauges plotted by a history card, each in its own serie.
+#[derive(Debug, serde::Deserialize)]
+#[serde(transparent)]
+pub struct Statistics(Vec<Statistic>);
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Self(vec![Statistic::Avg])
+    }
+}
+
+impl Statistics {
+    pub fn iter(&self) -> impl Iterator<Item = Statistic> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// The statistic is added to the name when several are plotted.
+    pub fn serie_name(&self, name: &str, statistic: Statistic) -> String {
+        if self.0.len() > 1 {
+            format!("{name} {}", statistic.as_str())
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Builds a serie for each statistic, ignoring the values that are not gauges.
+    pub fn gauge_series(
+        &self,
+        name: &str,
+        list: &[(u64, MetricValueAggr)],
+    ) -> Vec<(String, Vec<(u64, f64)>)> {
+        self.iter()
+            .map(|statistic| {
+                let values = list
+                    .iter()
+                    .filter_map(|(ts, value)| value.as_gauge().map(|v| (*ts, v.get(statistic))))
+                    .collect();
+                (self.serie_name(name, statistic), values)
+            })
+            .collect()
+    }
+}
+
+/// Metrics displayed by a card in rows keyed by the value of a tag, like the network
+/// interfaces or the watched processes.
+pub(crate) struct TaggedMetrics {
+    pub tag: &'static str,
+    pub metrics: &'static [&'static str],
+}
+
+impl TaggedMetrics {
+    /// Without tags, all the series of the metrics are collected.
+    pub fn collect(&self, buffer: &mut HashSet<MetricHeader>) {
+        for name in self.metrics {
+            buffer.insert(MetricHeader::new(*name));
+        }
+    }
+
+    /// The selected rows are displayed even when nothing got reported yet, all the tag
+    /// values being accepted when none is selected.
+    pub fn rows<'a, V>(
+        &'static self,
+        selected: &'a [String],
+        build: fn(&str) -> V,
+    ) -> TagRows<'a, V> {
+        TagRows {
+            definition: self,
+            selected,
+            build,
+            rows: selected
+                .iter()
+                .map(|name| (name.as_str(), build(name.as_str())))
+                .collect(),
+        }
+    }
+}
+
+pub(crate) struct TagRows<'a, V> {
+    definition: &'static TaggedMetrics,
+    selected: &'a [String],
+    build: fn(&str) -> V,
+    rows: BTreeMap<&'a str, V>,
+}
+
+impl<'a, V> TagRows<'a, V> {
+    /// Row of a serie, missing when the serie is not one of the card metrics or when its
+    /// tag value is not selected.
+    pub fn get_mut(&mut self, header: &'a MetricHeader) -> Option<&mut V> {
+        if !self.definition.metrics.contains(&header.name.as_ref()) {
+            return None;
+        }
+        let name = header
+            .tags
+            .get(self.definition.tag)
+            .and_then(|value| value.as_text())?;
+        if !self.selected.is_empty() && !self.selected.iter().any(|item| item == name) {
+            return None;
+        }
+        Some(self.rows.entry(name).or_insert_with(|| (self.build)(name)))
+    }
+
+    pub fn into_values(self) -> Vec<V> {
+        self.rows.into_values().collect()
+    }
+}
+
+/// History of the series matching a header, merged according to `group_by`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rollup {
+    pub header: MetricHeader,
+    pub group_by: GroupBy,
+    /// The count metrics are aggregated as rates.
+    pub rate: bool,
+}
+
 impl From<Size> for chezmoi_client::Size {
     fn from(value: Size) -> Self {
         match value {
@@ -39,18 +168,34 @@ impl From<Size> for chezmoi_client::Size {
     }
 }
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, serde::Deserialize)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub(crate) enum AnyCard {
     #[cfg(feature = "bluetooth")]
     AtcThermometer(atc_thermometer::AtcThermometerCard),
     #[cfg(feature = "bluetooth")]
+    BluetoothDevices(bluetooth_devices::BluetoothDevicesCard),
+    DevicesOnline(reachability::DevicesOnlineCard),
+    Disk(disk::DiskCard),
+    DiskHistory(disk::DiskHistoryCard),
+    MetricHistory(metric_history::MetricHistoryCard),
+    #[cfg(feature = "bluetooth")]
     Miflora(miflora::MifloraCard),
+    Network(network::NetworkCard),
+    NetworkHistory(network::NetworkHistoryCard),
+    Process(process::ProcessCard),
     SystemCpu(system::SystemCpuCard),
+    SystemCpuCores(system::SystemCpuCoresCard),
     SystemCpuHistory(system::SystemCpuHistoryCard),
+    SystemLoad(system::SystemLoadCard),
     SystemMemory(system::SystemMemoryCard),
     SystemMemoryHistory(system::SystemMemoryHistoryCard),
     SystemSwap(system::SystemSwapCard),
+    Thermal(thermal::ThermalCard),
+    ThermalHistory(thermal::ThermalHistoryCard),
+    #[cfg(feature = "http-probe")]
+    Uptime(uptime::UptimeCard),
 }
 
 impl AnyCard {
@@ -59,33 +204,86 @@ impl AnyCard {
             #[cfg(feature = "bluetooth")]
             Self::AtcThermometer(inner) => inner.collect_latest_metrics(buffer),
             #[cfg(feature = "bluetooth")]
+            Self::BluetoothDevices(inner) => inner.collect_latest_metrics(buffer),
+            Self::DevicesOnline(inner) => inner.collect_latest_metrics(buffer),
+            Self::Disk(inner) => inner.collect_latest_metrics(buffer),
+            #[cfg(feature = "bluetooth")]
             Self::Miflora(inner) => inner.collect_latest_metrics(buffer),
+            Self::Network(inner) => inner.collect_latest_metrics(buffer),
+            Self::Process(inner) => inner.collect_latest_metrics(buffer),
             Self::SystemCpu(inner) => inner.collect_latest_metrics(buffer),
+            Self::SystemCpuCores(inner) => inner.collect_latest_metrics(buffer),
+            Self::SystemLoad(inner) => inner.collect_latest_metrics(buffer),
             Self::SystemMemory(inner) => inner.collect_latest_metrics(buffer),
             Self::SystemSwap(inner) => inner.collect_latest_metrics(buffer),
+            Self::Thermal(inner) => inner.collect_latest_metrics(buffer),
+            #[cfg(feature = "http-probe")]
+            Self::Uptime(inner) => inner.collect_latest_metrics(buffer),
             _ => {}
         }
     }
 
     pub fn collect_history_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
         match self {
+            Self::DiskHistory(inner) => inner.collect_history_metrics(buffer),
+            Self::NetworkHistory(inner) => inner.collect_history_metrics(buffer),
             Self::SystemCpuHistory(inner) => inner.collect_history_metrics(buffer),
             Self::SystemMemoryHistory(inner) => inner.collect_history_metrics(buffer),
+            Self::ThermalHistory(inner) => inner.collect_history_metrics(buffer),
+            _ => {}
+        }
+    }
+
+    pub fn collect_rollups(&self, buffer: &mut HashSet<Rollup>) {
+        if let Self::MetricHistory(inner) = self {
+            inner.collect_rollups(buffer);
+        }
+    }
+
+    #[cfg_attr(not(feature = "http-probe"), allow(unused_variables))]
+    pub fn collect_availability_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        match self {
+            #[cfg(feature = "http-probe")]
+            Self::Uptime(inner) => inner.collect_availability_metrics(buffer),
+            _ => {}
+        }
+    }
+
+    pub fn collect_alert_rules(&self, buffer: &mut Vec<Rule>) {
+        match self {
+            #[cfg(feature = "bluetooth")]
+            Self::Miflora(inner) => inner.collect_alert_rules(buffer),
+            Self::Thermal(inner) => inner.collect_alert_rules(buffer),
             _ => {}
         }
     }
 
-    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard, String> {
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
         match self {
             #[cfg(feature = "bluetooth")]
             Self::AtcThermometer(inner) => inner.build_card(ctx).await,
             #[cfg(feature = "bluetooth")]
+            Self::BluetoothDevices(inner) => inner.build_card(ctx).await,
+            Self::DevicesOnline(inner) => inner.build_card(ctx).await,
+            Self::Disk(inner) => inner.build_card(ctx).await,
+            Self::DiskHistory(inner) => inner.build_card(ctx).await,
+            Self::MetricHistory(inner) => inner.build_card(ctx).await,
+            #[cfg(feature = "bluetooth")]
             Self::Miflora(inner) => inner.build_card(ctx).await,
+            Self::Network(inner) => inner.build_card(ctx).await,
+            Self::NetworkHistory(inner) => inner.build_card(ctx).await,
+            Self::Process(inner) => inner.build_card(ctx).await,
             Self::SystemCpu(inner) => inner.build_card(ctx).await,
+            Self::SystemCpuCores(inner) => inner.build_card(ctx).await,
             Self::SystemCpuHistory(inner) => inner.build_card(ctx).await,
+            Self::SystemLoad(inner) => inner.build_card(ctx).await,
             Self::SystemMemory(inner) => inner.build_card(ctx).await,
             Self::SystemMemoryHistory(inner) => inner.build_card(ctx).await,
             Self::SystemSwap(inner) => inner.build_card(ctx).await,
+            Self::Thermal(inner) => inner.build_card(ctx).await,
+            Self::ThermalHistory(inner) => inner.build_card(ctx).await,
+            #[cfg(feature = "http-probe")]
+            Self::Uptime(inner) => inner.build_card(ctx).await,
         }
     }
 }
@@ -109,6 +307,24 @@ impl Section {
             .iter()
             .for_each(|card| card.collect_history_metrics(buffer));
     }
+
+    pub fn collect_rollups(&self, buffer: &mut HashSet<Rollup>) {
+        self.cards
+            .iter()
+            .for_each(|card| card.collect_rollups(buffer));
+    }
+
+    pub fn collect_availability_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        self.cards
+            .iter()
+            .for_each(|card| card.collect_availability_metrics(buffer));
+    }
+
+    pub fn collect_alert_rules(&self, buffer: &mut Vec<Rule>) {
+        self.cards
+            .iter()
+            .for_each(|card| card.collect_alert_rules(buffer));
+    }
 }
 
 #[derive(Debug)]
@@ -117,6 +333,8 @@ pub struct BuilderContext {
     duration: TimePickerDuration,
     latest: HashMap<MetricHeader, (u64, MetricValue)>,
     history: HashMap<MetricHeader, Vec<(u64, MetricValueAggr)>>,
+    availability: HashMap<MetricHeader, f64>,
+    rollups: HashMap<Rollup, HashMap<MetricHeader, Vec<(u64, MetricValueAggr)>>>,
 }
 
 impl BuilderContext {
@@ -126,6 +344,8 @@ impl BuilderContext {
             duration,
             latest: Default::default(),
             history: Default::default(),
+            availability: Default::default(),
+            rollups: Default::default(),
         }
     }
 
@@ -140,6 +360,19 @@ impl BuilderContext {
             entry.push((metric.timerange.middle(), metric.value));
         });
     }
+
+    pub fn add_rollup(&mut self, rollup: Rollup, list: impl Iterator<Item = MetricAggr>) {
+        let history = self.rollups.entry(rollup).or_default();
+        list.for_each(|metric| {
+            let entry = history.entry(metric.header).or_default();
+            entry.push((metric.timerange.middle(), metric.value));
+        });
+    }
+
+    pub fn add_availabilities(&mut self, list: impl Iterator<Item = Availability>) {
+        self.availability
+            .extend(list.map(|item| (item.header, item.ratio)));
+    }
 }
 
 #[derive(Debug, Default, serde::Deserialize)]
@@ -165,7 +398,32 @@ impl Dashboard {
         Vec::from_iter(buf)
     }
 
-    pub async fn build_view(&self, ctx: BuilderContext) -> Result<dashboard::View, String> {
+    pub fn collect_rollups(&self) -> Vec<Rollup> {
+        let mut buf = HashSet::new();
+        self.sections
+            .iter()
+            .for_each(|sec| sec.collect_rollups(&mut buf));
+        Vec::from_iter(buf)
+    }
+
+    pub fn collect_availability_metrics(&self) -> Vec<MetricHeader> {
+        let mut buf = HashSet::new();
+        self.sections
+            .iter()
+            .for_each(|sec| sec.collect_availability_metrics(&mut buf));
+        Vec::from_iter(buf)
+    }
+
+    /// Rules firing when a value gets out of the range configured on a card.
+    pub fn collect_alert_rules(&self) -> Vec<Rule> {
+        let mut buf = Vec::new();
+        self.sections
+            .iter()
+            .for_each(|sec| sec.collect_alert_rules(&mut buf));
+        buf
+    }
+
+    pub async fn build_view(&self, ctx: BuilderContext) -> Result<dashboard::View<'_>, String> {
         let mut sections = Vec::with_capacity(self.sections.len());
         for section in self.sections.iter() {
             let mut vsec = dashboard::Section::new(section.name.as_ref());
@@ -177,3 +435,38 @@ impl Dashboard {
         Ok(dashboard::View::new(sections, ctx.duration))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chezmoi_agent::sensor::network::NETWORK_RECEIVED_RATE;
+    use chezmoi_database::metrics::MetricHeader;
+
+    use super::TaggedMetrics;
+
+    static TAGGED: TaggedMetrics = TaggedMetrics {
+        tag: "interface",
+        metrics: &[NETWORK_RECEIVED_RATE],
+    };
+
+    #[test]
+    fn should_only_list_rows_of_card_metrics() {
+        let eth0 = MetricHeader::new(NETWORK_RECEIVED_RATE).with_tag("interface", "eth0");
+        let wlan0 = MetricHeader::new(NETWORK_RECEIVED_RATE).with_tag("interface", "wlan0");
+        // a user defined metric using the same tag
+        let other = MetricHeader::new("exec.ping").with_tag("interface", "tun0");
+        let untagged = MetricHeader::new(NETWORK_RECEIVED_RATE);
+
+        let mut rows = TAGGED.rows(&[], |name| name.to_string());
+        for header in [&eth0, &wlan0, &other, &untagged] {
+            rows.get_mut(header);
+        }
+        assert_eq!(rows.into_values(), vec!["eth0", "wlan0"]);
+
+        let selected = vec!["lo".to_string(), "eth0".to_string()];
+        let mut rows = TAGGED.rows(&selected, |name| name.to_string());
+        assert!(rows.get_mut(&eth0).is_some());
+        assert!(rows.get_mut(&wlan0).is_none());
+        // the selected rows are kept without value
+        assert_eq!(rows.into_values(), vec!["eth0", "lo"]);
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chezmoi_agent::sensor::network::{NETWORK_RECEIVED_RATE, NETWORK_TRANSMITTED_RATE};
+use chezmoi_client::component::card::history_chart::Card as ClientHistoryChardCard;
+use chezmoi_client::component::card::network::{Card as ClientNetworkCard, InterfaceValues};
+use chezmoi_client::component::card::AnyCard as ClientAnyCard;
+use chezmoi_client::component::line_chart::Serie;
+use chezmoi_client::Dimension;
+use chezmoi_database::metrics::aggr::Statistic;
+use chezmoi_database::metrics::MetricHeader;
+
+use super::{BuilderContext, Size, Statistics, TaggedMetrics};
+
+static INTERFACES: TaggedMetrics = TaggedMetrics {
+    tag: "interface",
+    metrics: &[NETWORK_RECEIVED_RATE, NETWORK_TRANSMITTED_RATE],
+};
+
+fn interface(header: &MetricHeader) -> Option<&str> {
+    header
+        .tags
+        .get(INTERFACES.tag)
+        .and_then(|value| value.as_text())
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct NetworkCard {
+    /// Interfaces to display, all of them when empty.
+    #[serde(default)]
+    interfaces: Vec<String>,
+}
+
+impl From<NetworkCard> for super::AnyCard {
+    fn from(value: NetworkCard) -> Self {
+        Self::Network(value)
+    }
+}
+
+impl NetworkCard {
+    pub fn collect_latest_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        INTERFACES.collect(buffer);
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        let mut interfaces = INTERFACES.rows(&self.interfaces, |name| InterfaceValues {
+            name: name.to_string().into(),
+            received: None,
+            transmitted: None,
+        });
+        for (header, (_, value)) in ctx.latest.iter() {
+            let Some(entry) = interfaces.get_mut(header) else {
+                continue;
+            };
+            match header.name.as_ref() {
+                NETWORK_RECEIVED_RATE => entry.received = value.as_gauge(),
+                NETWORK_TRANSMITTED_RATE => entry.transmitted = value.as_gauge(),
+                _ => {}
+            }
+        }
+        Ok(ClientAnyCard::Network(ClientNetworkCard::new(
+            interfaces.into_values(),
+        )))
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct NetworkHistoryCard {
+    /// Interface to display, the sum of all of them when not provided.
+    #[serde(default)]
+    interface: Option<String>,
+    #[serde(default)]
+    statistics: Statistics,
+    #[serde(default = "Size::sm")]
+    height: Size,
+    #[serde(default = "Size::md")]
+    width: Size,
+}
+
+impl From<NetworkHistoryCard> for super::AnyCard {
+    fn from(value: NetworkHistoryCard) -> Self {
+        Self::NetworkHistory(value)
+    }
+}
+
+impl NetworkHistoryCard {
+    pub fn collect_history_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        INTERFACES.collect(buffer);
+    }
+
+    /// Sums the rates of the selected interfaces for each period.
+    fn values(&self, ctx: &BuilderContext, name: &str, statistic: Statistic) -> Vec<(u64, f64)> {
+        let mut values: BTreeMap<u64, f64> = BTreeMap::new();
+        ctx.history
+            .iter()
+            .filter(|(header, _)| header.name == name)
+            .filter(|(header, _)| match self.interface {
+                Some(ref expected) => interface(header) == Some(expected.as_str()),
+                None => true,
+            })
+            .flat_map(|(_, list)| list.iter())
+            .for_each(|(ts, value)| {
+                if let Some(gauge) = value.as_gauge() {
+                    *values.entry(*ts).or_default() += gauge.get(statistic);
+                }
+            });
+        values.into_iter().collect()
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        let title = match self.interface {
+            Some(ref name) => format!("Network {name}"),
+            None => String::from("Network"),
+        };
+        Ok(ClientAnyCard::HistoryChart(ClientHistoryChardCard::new(
+            title,
+            Dimension::new(self.width.into(), self.height.into()),
+            self.statistics
+                .iter()
+                .flat_map(|statistic| {
+                    [
+                        Serie::new(
+                            self.statistics.serie_name("Received", statistic),
+                            self.values(ctx, NETWORK_RECEIVED_RATE, statistic),
+                        ),
+                        Serie::new(
+                            self.statistics.serie_name("Transmitted", statistic),
+                            self.values(ctx, NETWORK_TRANSMITTED_RATE, statistic),
+                        ),
+                    ]
+                })
+                .collect(),
+            Some(ctx.window.0..ctx.window.1),
+            None,
+        )))
+    }
+}
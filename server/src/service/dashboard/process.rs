@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+use chezmoi_agent::sensor::process::{
+    PROCESS_CPU_USAGE, PROCESS_MEMORY, PROCESS_RESTARTS, PROCESS_RUNNING,
+};
+use chezmoi_client::component::card::process::{Card as ClientProcessCard, ProcessValues};
+use chezmoi_client::component::card::AnyCard as ClientAnyCard;
+use chezmoi_database::metrics::MetricHeader;
+
+use super::{BuilderContext, TaggedMetrics};
+
+static PROCESSES: TaggedMetrics = TaggedMetrics {
+    tag: "process",
+    metrics: &[
+        PROCESS_RUNNING,
+        PROCESS_CPU_USAGE,
+        PROCESS_MEMORY,
+        PROCESS_RESTARTS,
+    ],
+};
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct ProcessCard {
+    /// Processes to display, all the watched ones when empty.
+    #[serde(default)]
+    processes: Vec<String>,
+}
+
+impl From<ProcessCard> for super::AnyCard {
+    fn from(value: ProcessCard) -> Self {
+        Self::Process(value)
+    }
+}
+
+impl ProcessCard {
+    pub fn collect_latest_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        PROCESSES.collect(buffer);
+    }
+
+    fn build_rows(&self, ctx: &BuilderContext) -> Vec<ProcessValues<'_>> {
+        let mut processes = PROCESSES.rows(&self.processes, |name| ProcessValues {
+            name: name.to_string().into(),
+            running: None,
+            cpu: None,
+            memory: None,
+            restarts: None,
+        });
+        for (header, (_, value)) in ctx.latest.iter() {
+            let Some(entry) = processes.get_mut(header) else {
+                continue;
+            };
+            match header.name.as_ref() {
+                PROCESS_RUNNING => entry.running = value.as_bool(),
+                PROCESS_CPU_USAGE => entry.cpu = value.as_gauge(),
+                PROCESS_MEMORY => entry.memory = value.as_gauge(),
+                PROCESS_RESTARTS => entry.restarts = value.as_count(),
+                _ => {}
+            }
+        }
+        let mut processes = processes.into_values();
+        // the last usage reported before the process stopped is not relevant anymore
+        for process in processes.iter_mut() {
+            if process.running == Some(false) {
+                process.cpu = None;
+                process.memory = None;
+            }
+        }
+        processes
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        Ok(ClientAnyCard::Process(ClientProcessCard::new(
+            self.build_rows(ctx),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chezmoi_agent::sensor::process::{PROCESS_CPU_USAGE, PROCESS_MEMORY, PROCESS_RUNNING};
+    use chezmoi_client::view::dashboard::TimePickerDuration;
+    use chezmoi_database::metrics::entity::{Metric, MetricValue};
+    use chezmoi_database::metrics::MetricHeader;
+
+    use crate::service::dashboard::BuilderContext;
+
+    fn metric(name: &'static str, process: &str, value: MetricValue) -> Metric {
+        Metric {
+            timestamp: 0,
+            header: MetricHeader::new(name).with_tag("process", process.to_string()),
+            value,
+        }
+    }
+
+    #[test]
+    fn should_hide_usage_of_stopped_processes() {
+        let mut ctx = BuilderContext::new(TimePickerDuration::OneHour, (0, 60));
+        ctx.add_latests(
+            [
+                metric(PROCESS_RUNNING, "nginx", MetricValue::bool(true)),
+                metric(PROCESS_CPU_USAGE, "nginx", MetricValue::gauge(2.0)),
+                metric(PROCESS_MEMORY, "nginx", MetricValue::gauge(1024.0)),
+                metric(PROCESS_RUNNING, "sshd", MetricValue::bool(false)),
+                metric(PROCESS_CPU_USAGE, "sshd", MetricValue::gauge(1.0)),
+                metric(PROCESS_MEMORY, "sshd", MetricValue::gauge(512.0)),
+            ]
+            .into_iter(),
+        );
+        let card = super::ProcessCard::default();
+        let rows = card.build_rows(&ctx);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, "nginx");
+        assert_eq!(rows[0].cpu, Some(2.0));
+        assert_eq!(rows[0].memory, Some(1024.0));
+        assert_eq!(rows[1].name, "sshd");
+        assert_eq!(rows[1].cpu, None);
+        assert_eq!(rows[1].memory, None);
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashSet;
+
+use chezmoi_agent::sensor::reachability::{REACHABILITY_LATENCY, REACHABILITY_REACHABLE};
+use chezmoi_client::component::card::devices_online::{
+    Card as ClientDevicesOnlineCard, DeviceValues,
+};
+use chezmoi_client::component::card::AnyCard as ClientAnyCard;
+use chezmoi_database::metrics::MetricHeader;
+
+use super::{BuilderContext, TaggedMetrics};
+
+static HOSTS: TaggedMetrics = TaggedMetrics {
+    tag: "host",
+    metrics: &[REACHABILITY_REACHABLE, REACHABILITY_LATENCY],
+};
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct DevicesOnlineCard {
+    /// Hosts to display, all of them when empty.
+    #[serde(default)]
+    hosts: Vec<String>,
+}
+
+impl From<DevicesOnlineCard> for super::AnyCard {
+    fn from(value: DevicesOnlineCard) -> Self {
+        Self::DevicesOnline(value)
+    }
+}
+
+impl DevicesOnlineCard {
+    pub fn collect_latest_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        HOSTS.collect(buffer);
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        let mut devices = HOSTS.rows(&self.hosts, |name| DeviceValues {
+            name: name.to_string().into(),
+            reachable: None,
+            latency: None,
+        });
+        for (header, (_, value)) in ctx.latest.iter() {
+            let Some(entry) = devices.get_mut(header) else {
+                continue;
+            };
+            match header.name.as_ref() {
+                REACHABILITY_REACHABLE => entry.reachable = value.as_bool(),
+                REACHABILITY_LATENCY => entry.latency = value.as_gauge(),
+                _ => {}
+            }
+        }
+        Ok(ClientAnyCard::DevicesOnline(ClientDevicesOnlineCard::new(
+            devices.into_values(),
+        )))
+    }
+}
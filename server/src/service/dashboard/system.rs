@@ -2,14 +2,16 @@ use std::collections::HashSet;
 
 use chezmoi_client::component::card::history_chart::Card as ClientHistoryChardCard;
 use chezmoi_client::component::card::system_cpu::Card as ClientCpuCard;
+use chezmoi_client::component::card::system_cpu_cores::Card as ClientCpuCoresCard;
+use chezmoi_client::component::card::system_load::{Card as ClientLoadCard, Values as LoadValues};
 use chezmoi_client::component::card::system_memory::Card as ClientMemoryCard;
 use chezmoi_client::component::card::system_swap::Card as ClientSwapCard;
 use chezmoi_client::component::card::AnyCard as ClientAnyCard;
 use chezmoi_client::component::line_chart::Serie;
 use chezmoi_client::Dimension;
-use chezmoi_database::metrics::MetricHeader;
+use chezmoi_database::metrics::{MetricHeader, MetricTagValue};
 
-use super::{BuilderContext, Size};
+use super::{BuilderContext, Size, Statistics};
 
 fn find_gauge(name: &'static str, ctx: &BuilderContext) -> Option<f64> {
     let header = MetricHeader::new(name);
@@ -34,7 +36,7 @@ impl SystemCpuCard {
         ));
     }
 
-    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard, String> {
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
         Ok(ClientAnyCard::Cpu(ClientCpuCard::new(find_gauge(
             chezmoi_agent::sensor::system::GLOBAL_CPU_USAGE,
             ctx,
@@ -42,8 +44,44 @@ impl SystemCpuCard {
     }
 }
 
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct SystemCpuCoresCard;
+
+impl From<SystemCpuCoresCard> for super::AnyCard {
+    fn from(value: SystemCpuCoresCard) -> Self {
+        Self::SystemCpuCores(value)
+    }
+}
+
+impl SystemCpuCoresCard {
+    pub fn collect_latest_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        // without the core tag, the latest value of each core is fetched
+        buffer.insert(MetricHeader::new(chezmoi_agent::sensor::system::CPU_USAGE));
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        let cores = ctx
+            .latest
+            .iter()
+            .filter(|(header, _)| header.name == chezmoi_agent::sensor::system::CPU_USAGE)
+            .filter_map(|(header, (_, value))| {
+                // numbers are read back from the database as floats
+                let index = match header.tags.get("core")? {
+                    MetricTagValue::Int(index) => *index,
+                    MetricTagValue::Float(index) => *index as i64,
+                    _ => return None,
+                };
+                value.as_gauge().map(|v| (index, v))
+            })
+            .collect();
+        Ok(ClientAnyCard::CpuCores(ClientCpuCoresCard::new(cores)))
+    }
+}
+
 #[derive(Debug, serde::Deserialize)]
 pub(crate) struct SystemCpuHistoryCard {
+    #[serde(default)]
+    statistics: Statistics,
     #[serde(default = "Size::sm")]
     height: Size,
     #[serde(default = "Size::md")]
@@ -63,28 +101,62 @@ impl SystemCpuHistoryCard {
         ));
     }
 
-    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard, String> {
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
         let header = MetricHeader::new(chezmoi_agent::sensor::system::GLOBAL_CPU_USAGE);
-        let cpu_values = ctx
+        let list = ctx
             .history
             .get(&header)
-            .map(|list| {
-                list.iter()
-                    .filter_map(|(ts, value)| value.as_gauge().map(|v| (*ts, v.avg)))
-                    .collect()
-            })
+            .map(Vec::as_slice)
             .unwrap_or_default();
 
         Ok(ClientAnyCard::HistoryChart(ClientHistoryChardCard::new(
             "CPU usage",
             Dimension::new(self.width.into(), self.height.into()),
-            vec![Serie::new("CPU", cpu_values)],
+            self.statistics
+                .gauge_series("CPU", list)
+                .into_iter()
+                .map(|(name, values)| Serie::new(name, values))
+                .collect(),
             Some(ctx.window.0..ctx.window.1),
             Some(0.0..100.0),
         )))
     }
 }
 
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct SystemLoadCard;
+
+impl From<SystemLoadCard> for super::AnyCard {
+    fn from(value: SystemLoadCard) -> Self {
+        Self::SystemLoad(value)
+    }
+}
+
+impl SystemLoadCard {
+    pub fn collect_latest_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        for name in [
+            chezmoi_agent::sensor::system::LOAD_AVERAGE_1M,
+            chezmoi_agent::sensor::system::LOAD_AVERAGE_5M,
+            chezmoi_agent::sensor::system::LOAD_AVERAGE_15M,
+            chezmoi_agent::sensor::system::UPTIME,
+            chezmoi_agent::sensor::system::PROCESS_COUNT,
+        ] {
+            buffer.insert(MetricHeader::new(name));
+        }
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        Ok(ClientAnyCard::Load(ClientLoadCard::new(LoadValues {
+            load_1m: find_gauge(chezmoi_agent::sensor::system::LOAD_AVERAGE_1M, ctx),
+            load_5m: find_gauge(chezmoi_agent::sensor::system::LOAD_AVERAGE_5M, ctx),
+            load_15m: find_gauge(chezmoi_agent::sensor::system::LOAD_AVERAGE_15M, ctx),
+            uptime: find_gauge(chezmoi_agent::sensor::system::UPTIME, ctx).map(|v| v as u64),
+            process_count: find_gauge(chezmoi_agent::sensor::system::PROCESS_COUNT, ctx)
+                .map(|v| v as u64),
+        })))
+    }
+}
+
 #[derive(Debug, Default, serde::Deserialize)]
 pub(crate) struct SystemMemoryCard;
 
@@ -104,7 +176,7 @@ impl SystemMemoryCard {
         ));
     }
 
-    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard, String> {
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
         Ok(ClientAnyCard::Memory(ClientMemoryCard::new(
             find_gauge(chezmoi_agent::sensor::system::MEMORY_TOTAL, ctx),
             find_gauge(chezmoi_agent::sensor::system::MEMORY_USED, ctx),
@@ -114,6 +186,8 @@ impl SystemMemoryCard {
 
 #[derive(Debug, serde::Deserialize)]
 pub(crate) struct SystemMemoryHistoryCard {
+    #[serde(default)]
+    statistics: Statistics,
     #[serde(default = "Size::sm")]
     height: Size,
     #[serde(default = "Size::md")]
@@ -133,21 +207,21 @@ impl SystemMemoryHistoryCard {
         ));
     }
 
-    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard, String> {
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
         let header = MetricHeader::new(chezmoi_agent::sensor::system::MEMORY_RATIO);
-        let values: Vec<(u64, f64)> = ctx
+        let list = ctx
             .history
             .get(&header)
-            .map(|list| {
-                list.iter()
-                    .filter_map(|(ts, value)| value.as_gauge().map(|v| (*ts, v.avg)))
-                    .collect()
-            })
+            .map(Vec::as_slice)
             .unwrap_or_default();
         Ok(ClientAnyCard::HistoryChart(ClientHistoryChardCard::new(
             "Memory usage",
             Dimension::new(self.width.into(), self.height.into()),
-            vec![Serie::new("Memory usage", values)],
+            self.statistics
+                .gauge_series("Memory usage", list)
+                .into_iter()
+                .map(|(name, values)| Serie::new(name, values))
+                .collect(),
             Some(ctx.window.0..ctx.window.1),
             Some(0.0..100.0),
         )))
@@ -169,7 +243,7 @@ impl SystemSwapCard {
         buffer.insert(MetricHeader::new(chezmoi_agent::sensor::system::SWAP_TOTAL));
     }
 
-    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard, String> {
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
         Ok(ClientAnyCard::Swap(ClientSwapCard::new(
             find_gauge(chezmoi_agent::sensor::system::SWAP_TOTAL, ctx),
             find_gauge(chezmoi_agent::sensor::system::SWAP_USED, ctx),
@@ -0,0 +1,154 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chezmoi_agent::sensor::thermal::{HWMON_TEMPERATURE, THERMAL_ZONE_TEMPERATURE};
+use chezmoi_client::component::card::history_chart::Card as ClientHistoryChardCard;
+use chezmoi_client::component::card::thermal::{Card as ClientThermalCard, SensorValue};
+use chezmoi_client::component::card::AnyCard as ClientAnyCard;
+use chezmoi_client::component::line_chart::Serie;
+use chezmoi_client::Dimension;
+use chezmoi_database::metrics::MetricHeader;
+
+use super::{BuilderContext, Size, Statistics};
+use crate::service::alert::rule::{Condition, Rule};
+
+/// Human readable name of a temperature sensor, based on the tags set by the agent.
+fn sensor_name(header: &MetricHeader) -> Option<String> {
+    let label = header.tags.get("label").and_then(|value| value.as_text());
+    match header.name.as_ref() {
+        THERMAL_ZONE_TEMPERATURE => label
+            .or_else(|| header.tags.get("zone").and_then(|value| value.as_text()))
+            .map(String::from),
+        HWMON_TEMPERATURE => {
+            let chip = header.tags.get("chip").and_then(|value| value.as_text())?;
+            Some(match label {
+                Some(label) => format!("{chip} {label}"),
+                None => chip.to_string(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Without tags, all the series of both metrics are collected.
+fn collect_metrics(buffer: &mut HashSet<MetricHeader>) {
+    buffer.insert(MetricHeader::new(THERMAL_ZONE_TEMPERATURE));
+    buffer.insert(MetricHeader::new(HWMON_TEMPERATURE));
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct ThermalCard {
+    /// Temperature above which a sensor gets highlighted.
+    #[serde(default)]
+    max: Option<f64>,
+}
+
+impl From<ThermalCard> for super::AnyCard {
+    fn from(value: ThermalCard) -> Self {
+        Self::Thermal(value)
+    }
+}
+
+impl ThermalCard {
+    pub fn collect_latest_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        collect_metrics(buffer);
+    }
+
+    /// Fires for every sensor going above the highlighting limit.
+    pub fn collect_alert_rules(&self, buffer: &mut Vec<Rule>) {
+        let Some(threshold) = self.max else {
+            return;
+        };
+        for (label, metric) in [
+            ("thermal zone", THERMAL_ZONE_TEMPERATURE),
+            ("hwmon", HWMON_TEMPERATURE),
+        ] {
+            buffer.push(Rule::new(
+                format!("{label} temperature too high"),
+                MetricHeader::new(metric),
+                Condition::Above { threshold },
+            ));
+        }
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        let sensors = ctx
+            .latest
+            .iter()
+            .filter_map(|(header, (_, value))| {
+                Some(SensorValue {
+                    name: sensor_name(header)?.into(),
+                    temperature: value.as_gauge()?,
+                })
+            })
+            .collect();
+        Ok(ClientAnyCard::Thermal(
+            ClientThermalCard::new(sensors).with_max(self.max),
+        ))
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct ThermalHistoryCard {
+    #[serde(default)]
+    statistics: Statistics,
+    #[serde(default = "Size::sm")]
+    height: Size,
+    #[serde(default = "Size::md")]
+    width: Size,
+}
+
+impl From<ThermalHistoryCard> for super::AnyCard {
+    fn from(value: ThermalHistoryCard) -> Self {
+        Self::ThermalHistory(value)
+    }
+}
+
+impl ThermalHistoryCard {
+    pub fn collect_history_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        collect_metrics(buffer);
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        // sorted by name to keep the same colors between renderings
+        let series: BTreeMap<String, Vec<(u64, f64)>> = ctx
+            .history
+            .iter()
+            .filter_map(|(header, list)| {
+                let name = sensor_name(header)?;
+                Some(self.statistics.gauge_series(&name, list))
+            })
+            .flatten()
+            .collect();
+
+        Ok(ClientAnyCard::HistoryChart(ClientHistoryChardCard::new(
+            "Temperatures",
+            Dimension::new(self.width.into(), self.height.into()),
+            series
+                .into_iter()
+                .map(|(name, values)| Serie::new(name, values))
+                .collect(),
+            Some(ctx.window.0..ctx.window.1),
+            None,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chezmoi_agent::sensor::thermal::{HWMON_TEMPERATURE, THERMAL_ZONE_TEMPERATURE};
+
+    #[test]
+    fn should_alert_above_max() {
+        let mut rules = Vec::new();
+        super::ThermalCard::default().collect_alert_rules(&mut rules);
+        assert!(rules.is_empty());
+
+        let card = super::ThermalCard { max: Some(70.0) };
+        card.collect_alert_rules(&mut rules);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].header().name.as_ref(), THERMAL_ZONE_TEMPERATURE);
+        assert_eq!(rules[1].header().name.as_ref(), HWMON_TEMPERATURE);
+        assert_ne!(rules[0].name, rules[1].name);
+        assert_eq!(rules[0].condition.to_string(), "above 70");
+    }
+}
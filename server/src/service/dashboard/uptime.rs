@@ -0,0 +1,116 @@
+use std::collections::HashSet;
+
+use chezmoi_agent::sensor::http_probe::{HTTP_PROBE_RESPONSE_TIME, HTTP_PROBE_UP};
+use chezmoi_client::component::card::uptime::{Card as ClientUptimeCard, TargetValues};
+use chezmoi_client::component::card::AnyCard as ClientAnyCard;
+use chezmoi_database::metrics::MetricHeader;
+
+use super::{BuilderContext, TaggedMetrics};
+
+static TARGETS: TaggedMetrics = TaggedMetrics {
+    tag: "target",
+    metrics: &[HTTP_PROBE_UP, HTTP_PROBE_RESPONSE_TIME],
+};
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(crate) struct UptimeCard {
+    /// Targets to display, all of them when empty.
+    #[serde(default)]
+    targets: Vec<String>,
+}
+
+impl From<UptimeCard> for super::AnyCard {
+    fn from(value: UptimeCard) -> Self {
+        Self::Uptime(value)
+    }
+}
+
+impl UptimeCard {
+    pub fn collect_latest_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        TARGETS.collect(buffer);
+    }
+
+    pub fn collect_availability_metrics(&self, buffer: &mut HashSet<MetricHeader>) {
+        buffer.insert(MetricHeader::new(HTTP_PROBE_UP));
+    }
+
+    fn build_rows(&self, ctx: &BuilderContext) -> Vec<TargetValues<'static>> {
+        let mut targets = TARGETS.rows(&self.targets, |name| TargetValues {
+            name: name.to_string().into(),
+            up: None,
+            availability: None,
+            response_time: None,
+        });
+        for (header, (_, value)) in ctx.latest.iter() {
+            let Some(entry) = targets.get_mut(header) else {
+                continue;
+            };
+            match header.name.as_ref() {
+                HTTP_PROBE_UP => entry.up = value.as_bool(),
+                HTTP_PROBE_RESPONSE_TIME => entry.response_time = value.as_gauge(),
+                _ => {}
+            }
+        }
+        for (header, ratio) in ctx.availability.iter() {
+            if header.name != HTTP_PROBE_UP {
+                continue;
+            }
+            if let Some(entry) = targets.get_mut(header) {
+                entry.availability = Some(*ratio);
+            }
+        }
+        let mut targets = targets.into_values();
+        // the last response time measured before the target went down is misleading
+        for target in targets.iter_mut() {
+            if target.up == Some(false) {
+                target.response_time = None;
+            }
+        }
+        targets
+    }
+
+    pub async fn build_card(&self, ctx: &BuilderContext) -> Result<ClientAnyCard<'_>, String> {
+        Ok(ClientAnyCard::Uptime(ClientUptimeCard::new(
+            self.build_rows(ctx),
+            ctx.duration,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chezmoi_agent::sensor::http_probe::{HTTP_PROBE_RESPONSE_TIME, HTTP_PROBE_UP};
+    use chezmoi_client::view::dashboard::TimePickerDuration;
+    use chezmoi_database::metrics::entity::{Metric, MetricValue};
+    use chezmoi_database::metrics::MetricHeader;
+
+    use crate::service::dashboard::BuilderContext;
+
+    fn metric(name: &'static str, target: &str, value: MetricValue) -> Metric {
+        Metric {
+            timestamp: 0,
+            header: MetricHeader::new(name).with_tag("target", target.to_string()),
+            value,
+        }
+    }
+
+    #[test]
+    fn should_hide_response_time_of_down_targets() {
+        let mut ctx = BuilderContext::new(TimePickerDuration::OneHour, (0, 60));
+        ctx.add_latests(
+            [
+                metric(HTTP_PROBE_UP, "cloud", MetricValue::bool(true)),
+                metric(HTTP_PROBE_RESPONSE_TIME, "cloud", MetricValue::gauge(120.0)),
+                metric(HTTP_PROBE_UP, "wiki", MetricValue::bool(false)),
+                metric(HTTP_PROBE_RESPONSE_TIME, "wiki", MetricValue::gauge(80.0)),
+            ]
+            .into_iter(),
+        );
+        let rows = super::UptimeCard::default().build_rows(&ctx);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, "cloud");
+        assert_eq!(rows[0].response_time, Some(120.0));
+        assert_eq!(rows[1].name, "wiki");
+        assert_eq!(rows[1].response_time, None);
+    }
+}